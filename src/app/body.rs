@@ -0,0 +1,94 @@
+use spacemath::two::Point;
+
+// loads acting on the whole body rather than on edges, one set per load case
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BodyLoads {
+    // acceleration in m/s^2
+    pub gravity: Option<(f64, f64)>,
+    // angular speed in rad/s about a center in model units
+    pub rotation: Option<(f64, Point)>,
+}
+
+impl BodyLoads {
+    pub fn is_empty(&self) -> bool {
+        self.gravity.is_none() && self.rotation.is_none()
+    }
+}
+
+pub fn parse_gravity(x: &str) -> Result<(f64, f64), String> {
+    // expect "gx, gy" in m/s^2, e.g. "0, -9.81"
+    let (gx, gy) = x.split_once(',').ok_or("expected \"gx, gy\"")?;
+
+    Ok((number(gx)?, number(gy)?))
+}
+
+pub fn parse_rotation(x: &str) -> Result<(f64, Point), String> {
+    // expect "speed @ cx, cy" with the speed in rad/s, or rpm when suffixed
+    let (speed, center) = x.split_once('@').ok_or("expected \"speed @ cx, cy\"")?;
+
+    let speed = speed.trim();
+    let omega = match speed.strip_suffix("rpm") {
+        Some(rpm) => number(rpm)? * 2.0 * std::f64::consts::PI / 60.0,
+        None => number(speed.strip_suffix("rad/s").unwrap_or(speed))?,
+    };
+
+    let (cx, cy) = center
+        .split_once(',')
+        .ok_or("expected a center \"cx, cy\"")?;
+
+    Ok((omega, Point::new(number(cx)?, number(cy)?)))
+}
+
+fn number(x: &str) -> Result<f64, String> {
+    x.trim()
+        .parse()
+        .map_err(|_| format!("\"{}\" is not a number", x.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gravity() {
+        assert_eq!(parse_gravity("0, -9.81"), Ok((0.0, -9.81)));
+        assert_eq!(parse_gravity(" 1.5 ,2 "), Ok((1.5, 2.0)));
+
+        assert_eq!(
+            parse_gravity("9.81"),
+            Err("expected \"gx, gy\"".to_string())
+        );
+        assert_eq!(
+            parse_gravity("a, 1"),
+            Err("\"a\" is not a number".to_string())
+        );
+    }
+
+    #[test]
+    fn rotation() {
+        let (omega, c) = parse_rotation("100 @ 1, 2").unwrap();
+        assert_eq!((omega, c.x, c.y), (100.0, 1.0, 2.0));
+
+        let (omega, _) = parse_rotation("100 rad/s @ 0, 0").unwrap();
+        assert_eq!(omega, 100.0);
+
+        let (omega, _) = parse_rotation("60rpm @ 0, 0").unwrap();
+        assert!((omega - 2.0 * std::f64::consts::PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rotation_errors() {
+        assert_eq!(
+            parse_rotation("100"),
+            Err("expected \"speed @ cx, cy\"".to_string())
+        );
+        assert_eq!(
+            parse_rotation("100 @ 1"),
+            Err("expected a center \"cx, cy\"".to_string())
+        );
+        assert_eq!(
+            parse_rotation("fast @ 0, 0"),
+            Err("\"fast\" is not a number".to_string())
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use super::mark::Annotation;
+
+// force input, accepted forms
+//   1, 2    1 2    (1,2)    (1, 2) kN/m     components along x and y
+//   500 N @ 30deg    1.5 kN/m @ 0.5rad      magnitude and angle from +x (degrees by default)
+//   2 kN/m normal                           pressure along the edge normal
+// units scale to N (point loads) or N/m (edge tractions), plain numbers are taken as is
+pub fn parse_force(x: &str) -> Result<Annotation, String> {
+    let mut c = Cursor::new(x);
+
+    if c.at_end() {
+        return Err("enter a force".to_string());
+    }
+
+    let paren = c.eat('(');
+    let a = c.number()?;
+
+    if !paren {
+        let unit = c.unit()?;
+
+        if c.eat('@') {
+            let ang = c.number()?;
+            let ang = match c.word().as_deref() {
+                None | Some("deg") => ang.to_radians(),
+                Some("rad") => ang,
+                Some(w) => return Err(c.error(&format!("unknown angle unit \"{}\"", w))),
+            };
+            c.end()?;
+
+            let f = a * unit.unwrap_or(1.0);
+            return Ok(Annotation::Force(f * ang.cos(), f * ang.sin()));
+        }
+
+        if c.word().as_deref() == Some("normal") {
+            c.end()?;
+            return Ok(Annotation::Pressure(a * unit.unwrap_or(1.0)));
+        }
+
+        if unit.is_some() {
+            return Err(c.error("expected '@' or \"normal\" after a magnitude with units"));
+        }
+    }
+
+    // the separator between components is optional
+    c.eat(',');
+    let b = c.number()?;
+
+    if paren && !c.eat(')') {
+        return Err(c.error("expected ')'"));
+    }
+
+    let unit = c.unit()?.unwrap_or(1.0);
+    c.end()?;
+
+    Ok(Annotation::Force(a * unit, b * unit))
+}
+
+// pressure input, a magnitude with optional units, positive pushing into the part
+//   2e5    2 kN/m    2 kN/m normal
+pub fn parse_pressure(x: &str) -> Result<Annotation, String> {
+    let mut c = Cursor::new(x);
+
+    if c.at_end() {
+        return Err("enter a pressure".to_string());
+    }
+
+    let a = c.number()?;
+    let unit = c.unit()?.unwrap_or(1.0);
+
+    // the direction is implied, but a trailing "normal" reads the same as in a force
+    let start = c.pos;
+    if c.word().as_deref() != Some("normal") {
+        c.pos = start;
+    }
+    c.end()?;
+
+    Ok(Annotation::Pressure(a * unit))
+}
+
+const UNITS: [(&str, f64); 8] = [
+    ("N", 1.0),
+    ("kN", 1e3),
+    ("MN", 1e6),
+    ("N/m", 1.0),
+    ("kN/m", 1e3),
+    ("MN/m", 1e6),
+    ("N/mm", 1e3),
+    ("kN/mm", 1e6),
+];
+
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Cursor {
+    fn new(x: &str) -> Self {
+        let mut c = Self {
+            chars: x.chars().collect(),
+            pos: 0,
+        };
+        c.skip_ws();
+        c
+    }
+
+    fn error(&self, msg: &str) -> String {
+        // columns count from 1 like an editor
+        format!("{} at column {}", msg, self.pos + 1)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().map_or(false, |c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn end(&self) -> Result<(), String> {
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            self.skip_ws();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        let start = self.pos;
+        let digit = |c: Option<char>| c.map_or(false, |c| c.is_ascii_digit());
+
+        if self.peek() == Some('-') || self.peek() == Some('+') {
+            self.pos += 1;
+        }
+        while digit(self.peek()) || self.peek() == Some('.') {
+            self.pos += 1;
+        }
+
+        // exponent, only when digits follow so "2e" reads as 2 then a word
+        if self.peek() == Some('e') || self.peek() == Some('E') {
+            let mut j = self.pos + 1;
+            if matches!(self.chars.get(j), Some('-') | Some('+')) {
+                j += 1;
+            }
+            if digit(self.chars.get(j).copied()) {
+                self.pos = j;
+                while digit(self.peek()) {
+                    self.pos += 1;
+                }
+            }
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+        match text.parse() {
+            Ok(v) => {
+                self.skip_ws();
+                Ok(v)
+            }
+            Err(_) => {
+                self.pos = start;
+                Err(self.error("expected a number"))
+            }
+        }
+    }
+
+    fn word(&mut self) -> Option<String> {
+        // a run of letters and slashes, like kN/m or normal
+        // the degree sign reads as deg
+        if self.peek() == Some('°') {
+            self.pos += 1;
+            self.skip_ws();
+            return Some("deg".to_string());
+        }
+
+        let start = self.pos;
+        while self.peek().map_or(false, |c| c.is_alphabetic() || c == '/') {
+            self.pos += 1;
+        }
+
+        if self.pos == start {
+            return None;
+        }
+
+        let word = self.chars[start..self.pos].iter().collect();
+        self.skip_ws();
+        Some(word)
+    }
+
+    fn unit(&mut self) -> Result<Option<f64>, String> {
+        // an optional unit, leaving anything that isn't one (like normal) in place
+        let start = self.pos;
+
+        let word = match self.word() {
+            Some(w) if w != "normal" => w,
+            _ => {
+                self.pos = start;
+                return Ok(None);
+            }
+        };
+
+        match UNITS.iter().find(|(name, _)| *name == word) {
+            Some((_, factor)) => Ok(Some(*factor)),
+            None => {
+                self.pos = start;
+                let known: Vec<&str> = UNITS.iter().map(|(name, _)| *name).collect();
+                Err(self.error(&format!(
+                    "unknown unit \"{}\" (use one of {})",
+                    word,
+                    known.join(", ")
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn force(x: &str) -> (f64, f64) {
+        match parse_force(x) {
+            Ok(Annotation::Force(fx, fy)) => (fx, fy),
+            r => panic!("{:?} from {}", r, x),
+        }
+    }
+
+    fn close(a: (f64, f64), b: (f64, f64)) -> bool {
+        (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9
+    }
+
+    #[test]
+    fn components() {
+        assert_eq!(force("1, 2"), (1.0, 2.0));
+        assert_eq!(force("1 2"), (1.0, 2.0));
+        assert_eq!(force("(1,2)"), (1.0, 2.0));
+        assert_eq!(force("(1, 2) kN/m"), (1e3, 2e3));
+        assert_eq!(force("1.5e3, -2 N/mm"), (1.5e6, -2e3));
+    }
+
+    #[test]
+    fn polar() {
+        let (s, c) = 30f64.to_radians().sin_cos();
+        assert!(close(force("500 N @ 30deg"), (500.0 * c, 500.0 * s)));
+        assert!(close(force("500 N @ 30"), (500.0 * c, 500.0 * s)));
+        assert!(close(force("10 @ 90°"), (0.0, 10.0)));
+
+        let (s, c) = 0.5f64.sin_cos();
+        assert!(close(force("1.5 kN/m @ 0.5rad"), (1500.0 * c, 1500.0 * s)));
+    }
+
+    #[test]
+    fn normal() {
+        assert_eq!(parse_force("2 kN/m normal"), Ok(Annotation::Pressure(2e3)));
+        assert_eq!(parse_force("3 normal"), Ok(Annotation::Pressure(3.0)));
+    }
+
+    #[test]
+    fn force_errors() {
+        assert_eq!(parse_force("  "), Err("enter a force".to_string()));
+        assert_eq!(
+            parse_force("abc"),
+            Err("expected a number at column 1".to_string())
+        );
+        assert_eq!(
+            parse_force("(1, 2"),
+            Err("expected ')' at column 6".to_string())
+        );
+        assert_eq!(
+            parse_force("1, 2 3"),
+            Err("unexpected '3' at column 6".to_string())
+        );
+        assert_eq!(
+            parse_force("5 kN"),
+            Err("expected '@' or \"normal\" after a magnitude with units at column 5".to_string())
+        );
+
+        let e = parse_force("1, 2 lbf").unwrap_err();
+        assert!(e.starts_with("unknown unit \"lbf\""), "{}", e);
+        assert!(e.ends_with("at column 6"), "{}", e);
+
+        let e = parse_force("1 @ 30 grad").unwrap_err();
+        assert!(e.starts_with("unknown angle unit \"grad\""), "{}", e);
+    }
+
+    #[test]
+    fn pressure() {
+        assert_eq!(parse_pressure("2e5"), Ok(Annotation::Pressure(2e5)));
+        assert_eq!(parse_pressure("2 kN/m"), Ok(Annotation::Pressure(2e3)));
+        assert_eq!(
+            parse_pressure("2 kN/m normal"),
+            Ok(Annotation::Pressure(2e3))
+        );
+    }
+
+    #[test]
+    fn pressure_errors() {
+        assert_eq!(parse_pressure(""), Err("enter a pressure".to_string()));
+        assert_eq!(
+            parse_pressure("x"),
+            Err("expected a number at column 1".to_string())
+        );
+        assert_eq!(
+            parse_pressure("2 kN/m up"),
+            Err("unexpected 'u' at column 8".to_string())
+        );
+        assert_eq!(
+            parse_pressure("1, 2"),
+            Err("unexpected ',' at column 2".to_string())
+        );
+    }
+}
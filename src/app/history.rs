@@ -0,0 +1,44 @@
+use super::mark::MarkedModel;
+
+// how many steps back the history reaches, models are cloned whole so keep this modest
+const DEPTH: usize = 100;
+
+// snapshot-based undo/redo, a snapshot of the model is recorded ahead of every change
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Option<MarkedModel>>,
+    redo: Vec<Option<MarkedModel>>,
+}
+
+impl History {
+    pub fn record(&mut self, model: &Option<MarkedModel>) {
+        self.undo.push(model.clone());
+        if self.undo.len() > DEPTH {
+            self.undo.remove(0);
+        }
+
+        // a fresh change invalidates anything that was undone
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, model: &mut Option<MarkedModel>) -> bool {
+        // swap the current model for the last snapshot, returning whether there was one
+        match self.undo.pop() {
+            Some(prev) => {
+                self.redo.push(std::mem::replace(model, prev));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self, model: &mut Option<MarkedModel>) -> bool {
+        match self.redo.pop() {
+            Some(next) => {
+                self.undo.push(std::mem::replace(model, next));
+                true
+            }
+            None => false,
+        }
+    }
+}
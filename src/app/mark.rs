@@ -1,13 +1,41 @@
 use spacemath::two::boundary::{Boundary, Edge};
 use spacemath::two::dist::Dist;
+use spacemath::two::line::Segment;
 use spacemath::two::Point;
 
+use std::rc::Rc;
+
+use crate::geom::{
+    angle_between, curve_deviation, edge_length, edge_samples, edge_tangents, outward_normal,
+};
 use crate::reader::PartModel;
 
-#[derive(Debug, Clone, Copy)]
+use super::body::BodyLoads;
+use super::profile::{Profile, ProfileLoad};
+
+// neighbouring edges meeting at less than this angle (radians) continue a chain
+pub const CHAIN_TOLERANCE: f64 = 5.0 * std::f64::consts::PI / 180.0;
+
+// the load case every model starts with
+const DEFAULT_CASE: &str = "default";
+
+// unit normal components smaller than this count as zero when checking pressures
+const NORMAL_TOLERANCE: f64 = 1e-9;
+
+// edges this far off a symmetry line (relative to the model's size) still count as on it
+const SYMMETRY_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone)]
 pub struct Mark {
-    pub annot: Annotation,
+    // at most one constraint and one force, see Annotation::conflict
+    pub annots: Vec<Annotation>,
     pub inter: Interaction,
+
+    // loads of the inactive load cases, indexed by case
+    stash: Vec<Vec<Annotation>>,
+
+    // names of the groups this edge or vertex belongs to
+    pub groups: Vec<String>,
 }
 
 impl Mark {
@@ -15,8 +43,89 @@ impl Mark {
         self.inter = Interaction::Clicked;
     }
 
-    pub fn annotate(&mut self, annot: Annotation) {
-        self.annot = annot;
+    pub fn toggle_click(&mut self) {
+        self.inter = match self.inter {
+            Interaction::Clicked => Interaction::Ignored,
+            Interaction::Ignored => Interaction::Clicked,
+        };
+    }
+
+    pub fn annotate(&mut self, annot: Annotation) -> Result<(), String> {
+        // add an annotation, replacing any of the same kind
+        // Empty clears the mark, conflicting annotations are refused
+        if annot == Annotation::Empty {
+            self.annots.clear();
+            return Ok(());
+        }
+
+        let kept: Vec<Annotation> = self
+            .annots
+            .iter()
+            .filter(|a| !a.same_kind(&annot))
+            .cloned()
+            .collect();
+
+        if let Some(e) = kept.iter().find_map(|a| a.conflict(&annot)) {
+            return Err(e);
+        }
+
+        // the loads of the other cases have to fit when they come back
+        if let Some(e) = self.stash.iter().flatten().find_map(|a| a.conflict(&annot)) {
+            return Err(format!("{} in another load case", e));
+        }
+
+        self.annots = kept;
+        self.annots.push(annot);
+
+        Ok(())
+    }
+
+    pub fn sub_span(&self, a: f64, b: f64) -> Mark {
+        // the mark for the part of an edge between fractions a and b of its length
+        let sub = |annots: &Vec<Annotation>| {
+            annots
+                .iter()
+                .map(|annot| match annot {
+                    Annotation::Profile(pl) => Annotation::Profile(pl.sub_span(a, b)),
+                    annot => annot.clone(),
+                })
+                .collect()
+        };
+
+        Mark {
+            annots: sub(&self.annots),
+            inter: self.inter,
+            stash: self.stash.iter().map(sub).collect(),
+            groups: self.groups.clone(),
+        }
+    }
+
+    fn switch_case(&mut self, from: usize, to: usize) -> usize {
+        // stash the loads of case from and bring back those of case to
+        // returns how many were dropped for conflicting with a constraint added meanwhile
+        if self.stash.len() <= from.max(to) {
+            self.stash.resize(from.max(to) + 1, Vec::new());
+        }
+
+        let (loads, kept): (Vec<Annotation>, Vec<Annotation>) =
+            self.annots.drain(..).partition(|a| a.is_force());
+        self.annots = kept;
+        self.stash[from] = loads;
+
+        let mut dropped = 0;
+        for load in std::mem::take(&mut self.stash[to]) {
+            if self.annots.iter().any(|a| a.conflict(&load).is_some()) {
+                dropped += 1;
+            } else {
+                self.annots.push(load);
+            }
+        }
+
+        dropped
+    }
+
+    pub fn is_annotated(&self) -> bool {
+        !self.annots.is_empty()
     }
 
     pub fn is_clicked(&self) -> bool {
@@ -24,61 +133,208 @@ impl Mark {
     }
 
     pub fn is_force(&self) -> bool {
-        match self.annot {
-            Annotation::Force(_, _) => true,
-            _ => false,
-        }
+        self.annots.iter().any(|a| a.is_force())
     }
 
     pub fn is_constraint(&self) -> bool {
-        match self.annot {
-            Annotation::ConstrainX => true,
-            Annotation::ConstrainY => true,
-            Annotation::ConstrainXY => true,
-            Annotation::ConstrainTangent => true,
-            _ => false,
-        }
+        self.annots.iter().any(|a| a.is_constraint())
+    }
+
+    pub fn is_spring(&self) -> bool {
+        self.annots.iter().any(|a| a.is_spring())
     }
 }
 
 impl Default for Mark {
     fn default() -> Mark {
         Mark {
-            annot: Annotation::Empty,
+            annots: Vec::new(),
             inter: Interaction::Ignored,
+            stash: Vec::new(),
+            groups: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Annotation {
     Empty,
     ConstrainX,
     ConstrainY,
     ConstrainXY,
     ConstrainTangent,
+    // non-zero displacement in model units, None leaves that direction free
+    Displacement(Option<f64>, Option<f64>),
     Force(f64, f64),
+    // positive pressure pushes into the material along the edge normal
+    Pressure(f64),
+    // traction varying along a chain of edges
+    Profile(ProfileLoad),
+    // elastic foundation, normal and tangential stiffness per unit length
+    Spring(f64, f64),
 }
 
 impl Annotation {
-    pub fn parse_force(x: &str) -> Option<Self> {
-        // expect (0.05, 1.205)
-        // do this with nom when I have internet
-        if !x.starts_with("(") {
-            return None;
+    pub fn is_force(&self) -> bool {
+        match self {
+            Annotation::Force(_, _) => true,
+            Annotation::Pressure(_) => true,
+            Annotation::Profile(_) => true,
+            _ => false,
         }
+    }
 
-        if !x.ends_with(")") {
-            return None;
+    pub fn is_constraint(&self) -> bool {
+        match self {
+            Annotation::ConstrainX => true,
+            Annotation::ConstrainY => true,
+            Annotation::ConstrainXY => true,
+            Annotation::ConstrainTangent => true,
+            Annotation::Displacement(_, _) => true,
+            _ => false,
         }
+    }
 
-        let x = x.trim_matches(|c| c == '(' || c == ')');
+    pub fn is_spring(&self) -> bool {
+        matches!(self, Annotation::Spring(_, _))
+    }
 
-        let mut vals = x.split(", ");
-        let x = vals.next()?.parse().ok()?;
-        let y = vals.next()?.parse().ok()?;
+    pub fn fits_vertex(&self) -> bool {
+        // point loads and point supports; pressure and tangency need an edge
+        match self {
+            Annotation::Force(_, _) => true,
+            Annotation::ConstrainX => true,
+            Annotation::ConstrainY => true,
+            Annotation::ConstrainXY => true,
+            _ => false,
+        }
+    }
+
+    fn same_kind(&self, other: &Annotation) -> bool {
+        // an edge holds one annotation of each kind
+        (self.is_force() && other.is_force())
+            || (self.is_constraint() && other.is_constraint())
+            || (self.is_spring() && other.is_spring())
+    }
+
+    pub fn conflict(&self, other: &Annotation) -> Option<String> {
+        // a rigid support leaves nothing for a spring on the same edge to do
+        if (self.is_spring() && other.is_constraint())
+            || (self.is_constraint() && other.is_spring())
+        {
+            return Some(format!("{:?} conflicts with {:?}", self, other));
+        }
+
+        // a force along a constrained direction would only be carried by the support
+        let (c, f) = match (self, other) {
+            (c, f) | (f, c) if c.is_constraint() && f.is_force() => (c, f),
+            _ => return None,
+        };
+
+        let (fix_x, fix_y) = c.fixes();
 
-        Some(Self::Force(x, y))
+        let clash = match f {
+            &Annotation::Force(x, y) => (fix_x && x != 0.0) || (fix_y && y != 0.0),
+            // pressure acts along the normal, which these constraints fix
+            Annotation::Pressure(_) => (fix_x && fix_y) || *c == Annotation::ConstrainTangent,
+            Annotation::Profile(pl) => {
+                let (x, y) = pl.profile.acts_along();
+                (fix_x && x) || (fix_y && y)
+            }
+            _ => false,
+        };
+
+        if clash {
+            Some(format!("{:?} conflicts with {:?}", c, f))
+        } else {
+            None
+        }
+    }
+
+    fn fixes(&self) -> (bool, bool) {
+        // the global directions a constraint fixes, tangent constraints fix neither alone
+        match self {
+            Annotation::ConstrainX => (true, false),
+            Annotation::ConstrainY => (false, true),
+            Annotation::ConstrainXY => (true, true),
+            Annotation::Displacement(dx, dy) => (dx.is_some(), dy.is_some()),
+            _ => (false, false),
+        }
+    }
+
+    fn normal_conflict(&self, other: &Annotation, edge: &Edge) -> Option<String> {
+        // a pressure pushes along the edge's normal, so a constraint fixing any direction the
+        // normal has a component in would carry it, e.g. ConstrainX on a vertical edge
+        let c = match (self, other) {
+            (c, Annotation::Pressure(_)) | (Annotation::Pressure(_), c) if c.is_constraint() => c,
+            _ => return None,
+        };
+
+        // an arc's normal turns through both directions
+        let (along_x, along_y) = match edge {
+            Edge::Arc(_) => (true, true),
+            Edge::Segment(_) => {
+                let n = outward_normal(edge);
+                (n.x.abs() > NORMAL_TOLERANCE, n.y.abs() > NORMAL_TOLERANCE)
+            }
+        };
+
+        let (fix_x, fix_y) = c.fixes();
+        if (fix_x && along_x) || (fix_y && along_y) {
+            Some(format!(
+                "{:?} conflicts with {:?} on this edge",
+                self, other
+            ))
+        } else {
+            None
+        }
+    }
+
+    pub fn symmetry(line: &Edge) -> Self {
+        // the constraint holding a symmetry line's edges on it: no motion along its normal
+        let d = line.q() - line.p();
+
+        if d.y.abs() <= SYMMETRY_TOLERANCE * d.x.abs() {
+            Annotation::ConstrainY
+        } else if d.x.abs() <= SYMMETRY_TOLERANCE * d.y.abs() {
+            Annotation::ConstrainX
+        } else {
+            Annotation::ConstrainTangent
+        }
+    }
+
+    pub fn parse_force(x: &str) -> Result<Self, String> {
+        // see force.rs for the accepted forms
+        super::force::parse_force(x)
+    }
+
+    pub fn parse_pressure(x: &str) -> Result<Self, String> {
+        super::force::parse_pressure(x)
+    }
+
+    pub fn parse_displacement(x: &str) -> Option<Self> {
+        // expect "0.1, _" where _ (or free) leaves a direction unconstrained
+        let (dx, dy) = x.trim().split_once(',')?;
+
+        let component = |v: &str| match v.trim() {
+            "_" | "free" => Some(None),
+            v => v.parse().ok().map(Some),
+        };
+
+        match (component(dx)?, component(dy)?) {
+            (None, None) => None,
+            (dx, dy) => Some(Self::Displacement(dx, dy)),
+        }
+    }
+
+    pub fn parse_spring(x: &str) -> Option<Self> {
+        // expect "kn, kt"
+        let (kn, kt) = x.trim().split_once(',')?;
+
+        Some(Self::Spring(
+            kn.trim().parse().ok()?,
+            kt.trim().parse().ok()?,
+        ))
     }
 }
 
@@ -88,11 +344,27 @@ pub enum Interaction {
     Clicked,
 }
 
+// what a mark is attached to, for reporting
+#[derive(Debug, Clone, Copy)]
+pub enum Feature {
+    Edge(Edge),
+    Vertex(Point),
+}
+
 // a boundary with
 #[derive(Debug, Clone)]
 pub struct MarkedBound {
     bound: Boundary,
     marks: Vec<Mark>,
+
+    // vertex i is the start of edge i
+    vertex_marks: Vec<Mark>,
+
+    // the curve each edge was cut from, the edge itself until segmentified
+    parents: Vec<Edge>,
+
+    // index of the region this bound belongs to, see MarkedModel::regions
+    region: usize,
 }
 
 impl MarkedBound {
@@ -100,13 +372,53 @@ impl MarkedBound {
         self.bound.edges().zip(self.marks.iter())
     }
 
-    pub fn from_edges_and_marks(edges: Vec<Edge>, marks: Vec<Mark>) -> Self {
+    pub fn vertices_and_marks<'a>(&'a self) -> impl Iterator<Item = (Point, &'a Mark)> + 'a {
+        self.bound
+            .edges()
+            .map(|e| e.p())
+            .zip(self.vertex_marks.iter())
+    }
+
+    pub fn parents(&self) -> &[Edge] {
+        &self.parents
+    }
+
+    pub fn region(&self) -> usize {
+        self.region
+    }
+
+    pub fn from_edges_and_marks(
+        edges: Vec<Edge>,
+        marks: Vec<Mark>,
+        vertex_marks: Vec<Mark>,
+        parents: Vec<Edge>,
+    ) -> Self {
         Self {
             bound: Boundary::new(edges),
             marks,
+            vertex_marks,
+            parents,
+            region: 0,
         }
     }
 
+    fn vertex_near(&self, pos: Point, tol: f64) -> Option<(usize, f64)> {
+        // index and distance of the closest vertex within tol of pos
+        // a vertex reaches at most a quarter of the way along its edges, so the middle of
+        // a short edge still picks the edge
+        let edges: Vec<&Edge> = self.bound.edges().collect();
+        let n = edges.len();
+
+        (0..n)
+            .map(|i| {
+                let shortest = edge_length(edges[i]).min(edge_length(edges[(i + n - 1) % n]));
+                (i, edges[i].p().dist(pos), tol.min(shortest / 4.0))
+            })
+            .filter(|&(_, d, reach)| d <= reach)
+            .map(|(i, d, _)| (i, d))
+            .min_by(|x, y| x.1.partial_cmp(&y.1).unwrap())
+    }
+
     pub fn pos_edge_index(&self, pos: Point) -> usize {
         self.bound
             .edges()
@@ -121,30 +433,203 @@ impl MarkedBound {
         let i = self.pos_edge_index(pos);
         self.marks[i].click();
 
-        return (*self.bound.edges().nth(i).unwrap(), self.marks[i]);
+        return (*self.bound.edges().nth(i).unwrap(), self.marks[i].clone());
     }
 
-    pub fn annotate_clicked(&mut self, annot: Annotation) -> Vec<(Edge, Mark)> {
+    pub fn toggle_at_pos(&mut self, pos: Point) -> (Edge, Mark) {
+        // flip the selection of the edge closest to the given point
+        let i = self.pos_edge_index(pos);
+        self.marks[i].toggle_click();
+
+        return (*self.bound.edges().nth(i).unwrap(), self.marks[i].clone());
+    }
+
+    pub fn click_in_box(&mut self, min: Point, max: Point) -> Vec<(Edge, Mark)> {
+        // click every edge lying entirely within the box
+        let inside = |p: Point| p.x >= min.x && p.x <= max.x && p.y >= min.y && p.y <= max.y;
+
         let mut res = Vec::new();
 
         for (e, m) in self.bound.edges().zip(self.marks.iter_mut()) {
+            // sample along the edge so arcs bulging out of the box are excluded
+            if edge_samples(e, 8).into_iter().all(|p| inside(p)) {
+                m.click();
+                res.push((*e, m.clone()));
+            }
+        }
+
+        res
+    }
+
+    pub fn click_chains(&mut self, tol: f64) -> Vec<(Edge, Mark)> {
+        // extend the selection along tangent-continuous neighbours of clicked edges
+        // edges run head to tail around the boundary, so neighbours are adjacent indices
+        let edges: Vec<Edge> = self.bound.edges().cloned().collect();
+        let n = edges.len();
+
+        let continues = |i: usize| {
+            // whether edge i flows smoothly into edge i + 1
+            let (_, out) = edge_tangents(&edges[i]);
+            let (inc, _) = edge_tangents(&edges[(i + 1) % n]);
+            angle_between(out, inc) <= tol
+        };
+
+        let mut chained = vec![false; n];
+
+        for i in (0..n).filter(|&i| self.marks[i].is_clicked()) {
+            let mut j = i;
+            while continues(j) && (j + 1) % n != i {
+                j = (j + 1) % n;
+                chained[j] = true;
+            }
+
+            let mut j = i;
+            while continues((j + n - 1) % n) && (j + n - 1) % n != i {
+                j = (j + n - 1) % n;
+                chained[j] = true;
+            }
+        }
+
+        let mut res = Vec::new();
+
+        for (i, c) in chained.into_iter().enumerate() {
+            if c && !self.marks[i].is_clicked() {
+                self.marks[i].click();
+                res.push((edges[i], self.marks[i].clone()));
+            }
+        }
+
+        res
+    }
+
+    pub fn click_all(&mut self) -> Vec<(Edge, Mark)> {
+        for m in self.marks.iter_mut() {
+            m.click();
+        }
+
+        self.edges_and_marks()
+            .map(|(e, m)| (*e, m.clone()))
+            .collect()
+    }
+
+    pub fn any_clicked(&self) -> bool {
+        self.marks.iter().any(|m| m.is_clicked())
+    }
+
+    fn clicked_line(&self) -> Option<Edge> {
+        // the first selected straight edge
+        self.edges_and_marks()
+            .find(|(e, m)| m.is_clicked() && matches!(e, Edge::Segment(_)))
+            .map(|(e, _)| *e)
+    }
+
+    fn click_on_line(&mut self, line: &Edge, tol: f64) -> usize {
+        // select the straight edges lying within tol of the infinite line through line
+        let mut count = 0;
+
+        for (e, m) in self.bound.edges().zip(self.marks.iter_mut()) {
+            if matches!(e, Edge::Segment(_)) && curve_deviation(e.p(), e.q(), line) <= tol {
+                m.click();
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    pub fn annotate_clicked(&mut self, annot: &Annotation) -> Vec<(Feature, Result<Mark, String>)> {
+        let mut res = Vec::new();
+
+        for (e, m) in self.bound.edges().zip(self.marks.iter_mut()) {
+            if m.is_clicked() {
+                // pressures depend on the edge's direction, which Mark::annotate can't see
+                let normal_conflict = m
+                    .annots
+                    .iter()
+                    .filter(|a| !a.same_kind(annot))
+                    .chain(m.stash.iter().flatten())
+                    .find_map(|a| a.normal_conflict(annot, e));
+
+                // the writers treat edges as chords, whose normal is wrong for a whole arc
+                let r = if *annot == Annotation::ConstrainTangent && matches!(e, Edge::Arc(_)) {
+                    Err("segmentify arcs before constraining them tangentially".to_string())
+                } else if let Some(err) = normal_conflict {
+                    Err(err)
+                } else {
+                    m.annotate(annot.clone()).map(|_| m.clone())
+                };
+                res.push((Feature::Edge(*e), r));
+            }
+        }
+
+        for (e, m) in self.bound.edges().zip(self.vertex_marks.iter_mut()) {
             if m.is_clicked() {
-                m.annotate(annot);
-                res.push((*e, *m));
+                let r = if *annot == Annotation::Empty || annot.fits_vertex() {
+                    m.annotate(annot.clone()).map(|_| m.clone())
+                } else {
+                    Err(format!("{:?} can't be applied to a vertex", annot))
+                };
+                res.push((Feature::Vertex(e.p()), r));
             }
         }
 
         res
     }
 
+    fn clicked_spans(&self, s0: f64) -> (Vec<(usize, (f64, f64))>, f64) {
+        // arc length spans of the clicked edges, continuing a chain measured from s0
+        // starts at the head of a run of clicked edges so runs over the seam stay in order
+        let n = self.marks.len();
+        let start = (0..n)
+            .find(|&i| self.marks[i].is_clicked() && !self.marks[(i + n - 1) % n].is_clicked())
+            .unwrap_or(0);
+
+        let edges: Vec<&Edge> = self.bound.edges().collect();
+
+        let mut s = s0;
+        let mut res = Vec::new();
+
+        for i in (0..n).map(|k| (start + k) % n) {
+            if self.marks[i].is_clicked() {
+                let len = edge_length(edges[i]);
+                res.push((i, (s, s + len)));
+                s += len;
+            }
+        }
+
+        (res, s)
+    }
+
+    pub fn clicked_length(&self) -> f64 {
+        self.edges_and_marks()
+            .filter(|(_, m)| m.is_clicked())
+            .map(|(e, _)| edge_length(e))
+            .sum()
+    }
+
     pub fn clear_interactions(&mut self) {
-        for m in self.marks.iter_mut() {
+        for m in self.marks.iter_mut().chain(self.vertex_marks.iter_mut()) {
             m.inter = Interaction::Ignored
         }
     }
 
-    pub fn into_parts(self) -> (Boundary, Vec<Mark>) {
-        (self.bound, self.marks)
+    pub fn clear_annotations(&mut self) -> usize {
+        // reset every edge and vertex to Empty, returning how many were annotated
+        let mut count = 0;
+
+        // the loads stashed for other cases go too
+        let marks = self.marks.iter_mut().chain(self.vertex_marks.iter_mut());
+        for m in marks.filter(|m| m.is_annotated() || m.stash.iter().any(|s| !s.is_empty())) {
+            m.annots.clear();
+            m.stash.clear();
+            count += 1;
+        }
+
+        count
+    }
+
+    pub fn into_parts(self) -> (Boundary, Vec<Mark>, Vec<Mark>, Vec<Edge>) {
+        (self.bound, self.marks, self.vertex_marks, self.parents)
     }
 
     pub fn segmentify(self, len: f64) -> Self {
@@ -153,44 +638,95 @@ impl MarkedBound {
 
         let mut res_edges = Vec::new();
         let mut res_marks = Vec::new();
+        let mut res_vertex_marks = Vec::new();
+        let mut res_parents = Vec::new();
+
+        let edges = self
+            .edges_and_marks()
+            .zip(self.vertex_marks.iter())
+            .zip(self.parents.iter());
 
-        for (e, m) in self.edges_and_marks() {
+        for (((e, m), vm), parent) in edges {
             let edge_segments = e.into_segments(len);
 
-            for _ in 0..edge_segments.len() {
-                res_marks.push(m.clone());
+            // children cover consecutive parts of the parent, which matters for profiles
+            let lens: Vec<f64> = edge_segments.iter().map(edge_length).collect();
+            let total: f64 = lens.iter().sum();
+
+            let mut acc = 0.0;
+            for l in lens {
+                res_marks.push(m.sub_span(acc / total, (acc + l) / total));
+                acc += l;
+            }
+
+            // the edge's start keeps its mark, new interior vertices start blank
+            res_vertex_marks.push(vm.clone());
+            for _ in 1..edge_segments.len() {
+                res_vertex_marks.push(Mark::default());
             }
 
+            // every piece remembers the original curve, so repeated calls keep it
+            res_parents.extend(edge_segments.iter().map(|_| *parent));
+
             res_edges.extend(edge_segments);
         }
 
-        Self::from_edges_and_marks(res_edges, res_marks)
+        Self {
+            region: self.region,
+            ..Self::from_edges_and_marks(res_edges, res_marks, res_vertex_marks, res_parents)
+        }
     }
 }
 
 impl From<Boundary> for MarkedBound {
     fn from(bound: Boundary) -> Self {
         let n = bound.num_edges();
+        let parents = bound.edges().copied().collect();
 
         MarkedBound {
             bound,
             marks: vec![Mark::default(); n],
+            vertex_marks: vec![Mark::default(); n],
+            parents,
+            region: 0,
         }
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct MarkedModel(Vec<MarkedBound>);
+pub struct MarkedModel {
+    bounds: Vec<MarkedBound>,
+
+    // named load cases share the constraints, only the active case's loads sit in the marks
+    cases: Vec<String>,
+    active: usize,
+
+    // gravity and rotation, per load case
+    body_loads: Vec<BodyLoads>,
+
+    // named selections, membership is kept in the marks
+    groups: Vec<String>,
+
+    // properties per region, unset ones fall back to the app's material and thickness
+    regions: Vec<RegionProps>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RegionProps {
+    pub material: Option<String>,
+    // in model units, like the geometry
+    pub thickness: Option<f64>,
+}
 
 impl MarkedModel {
     pub fn bounds<'a>(&'a self) -> impl Iterator<Item = &'a MarkedBound> + 'a {
-        self.0.iter()
+        self.bounds.iter()
     }
 
     pub fn click_at_pos(&mut self, pos: Point) -> (Edge, Mark) {
         // apply the mark to the closest boundary to the given point
         let b = self
-            .0
+            .bounds
             .iter_mut()
             .min_by(|x, y| x.bound.dist(pos).partial_cmp(&y.bound.dist(pos)).unwrap())
             .unwrap();
@@ -198,32 +734,349 @@ impl MarkedModel {
         b.click_at_pos(pos)
     }
 
-    pub fn annotate_clicked(&mut self, annot: Annotation) -> Vec<(Edge, Mark)> {
+    pub fn toggle_at_pos(&mut self, pos: Point) -> (Edge, Mark) {
+        let b = self
+            .bounds
+            .iter_mut()
+            .min_by(|x, y| x.bound.dist(pos).partial_cmp(&y.bound.dist(pos)).unwrap())
+            .unwrap();
+
+        b.toggle_at_pos(pos)
+    }
+
+    fn vertex_mark_at_pos(&mut self, pos: Point, tol: f64) -> Option<(Point, &mut Mark)> {
+        // the closest vertex within tol of the given point, over all boundaries
+        let (b, i) = self
+            .bounds
+            .iter()
+            .enumerate()
+            .filter_map(|(b, bound)| bound.vertex_near(pos, tol).map(|(i, d)| (b, i, d)))
+            .min_by(|x, y| x.2.partial_cmp(&y.2).unwrap())
+            .map(|(b, i, _)| (b, i))?;
+
+        let bound = &mut self.bounds[b];
+        let p = bound.bound.edges().nth(i).unwrap().p();
+
+        Some((p, &mut bound.vertex_marks[i]))
+    }
+
+    pub fn click_vertex_at_pos(&mut self, pos: Point, tol: f64) -> Option<(Point, Mark)> {
+        self.vertex_mark_at_pos(pos, tol).map(|(p, m)| {
+            m.click();
+            (p, m.clone())
+        })
+    }
+
+    pub fn toggle_vertex_at_pos(&mut self, pos: Point, tol: f64) -> Option<(Point, Mark)> {
+        self.vertex_mark_at_pos(pos, tol).map(|(p, m)| {
+            m.toggle_click();
+            (p, m.clone())
+        })
+    }
+
+    pub fn click_chains(&mut self, tol: f64) -> Vec<(Edge, Mark)> {
+        let mut res = Vec::new();
+
+        for b in self.bounds.iter_mut() {
+            res.extend(b.click_chains(tol));
+        }
+
+        res
+    }
+
+    pub fn click_boundaries(&mut self) -> Vec<(Edge, Mark)> {
+        // select every edge of each boundary holding a clicked edge
+        let mut res = Vec::new();
+
+        for b in self.bounds.iter_mut().filter(|b| b.any_clicked()) {
+            res.extend(b.click_all());
+        }
+
+        res
+    }
+
+    pub fn click_symmetry_line(&mut self) -> Option<(Edge, usize)> {
+        // the first selected straight edge defines the line
+        // every straight edge on it, in any boundary, becomes the selection
+        let line = self.bounds.iter().find_map(|b| b.clicked_line())?;
+        let count = self.click_on_line(&line);
+
+        Some((line, count))
+    }
+
+    pub fn click_axis(&mut self) -> usize {
+        // select the straight edges on the axis of revolution, x = 0
+        let axis = Segment::new(Point::new(0.0, 0.0), Point::new(0.0, 1.0)).into();
+        self.click_on_line(&axis)
+    }
+
+    fn click_on_line(&mut self, line: &Edge) -> usize {
+        let (min, max) = self.bounding_box();
+        let tol = SYMMETRY_TOLERANCE * min.dist(max);
+
+        self.clear_interactions();
+        self.bounds
+            .iter_mut()
+            .map(|b| b.click_on_line(line, tol))
+            .sum()
+    }
+
+    pub fn off_axis(&self) -> bool {
+        // axisymmetric models must keep to the x >= 0 side of the axis
+        let (min, max) = self.bounding_box();
+        min.x < -SYMMETRY_TOLERANCE * min.dist(max)
+    }
+
+    pub fn click_in_box(&mut self, a: Point, b: Point) -> Vec<(Edge, Mark)> {
+        // corners may come in any order
+        let min = Point::new(a.x.min(b.x), a.y.min(b.y));
+        let max = Point::new(a.x.max(b.x), a.y.max(b.y));
+
+        let mut res = Vec::new();
+
+        for bound in self.bounds.iter_mut() {
+            res.extend(bound.click_in_box(min, max));
+        }
+
+        res
+    }
+
+    pub fn annotate_clicked(&mut self, annot: &Annotation) -> Vec<(Feature, Result<Mark, String>)> {
         let mut res = Vec::new();
 
-        for b in self.0.iter_mut() {
+        for b in self.bounds.iter_mut() {
             res.extend(b.annotate_clicked(annot));
         }
 
         res
     }
 
+    pub fn annotate_clicked_profile(
+        &mut self,
+        profile: Profile,
+    ) -> Vec<(Feature, Result<Mark, String>)> {
+        // the clicked edges make up one chain, measured in boundary order
+        let profile = Rc::new(profile);
+
+        let mut s = 0.0;
+        let mut spans = Vec::new();
+        for b in self.bounds.iter() {
+            let (b_spans, end) = b.clicked_spans(s);
+            spans.push(b_spans);
+            s = end;
+        }
+
+        let mut res = Vec::new();
+
+        for (b, b_spans) in self.bounds.iter_mut().zip(spans) {
+            for (i, span) in b_spans {
+                let annot = Annotation::Profile(ProfileLoad {
+                    profile: profile.clone(),
+                    span,
+                    total: s,
+                });
+
+                let r = b.marks[i].annotate(annot).map(|_| b.marks[i].clone());
+                res.push((Feature::Edge(*b.bound.edges().nth(i).unwrap()), r));
+            }
+        }
+
+        res
+    }
+
     pub fn clear_interactions(&mut self) {
-        for b in self.0.iter_mut() {
+        for b in self.bounds.iter_mut() {
             b.clear_interactions()
         }
     }
 
+    pub fn clicked_length(&self) -> f64 {
+        // summed true length of the selected edges, in model units
+        self.bounds.iter().map(|b| b.clicked_length()).sum()
+    }
+
+    pub fn any_vertex_clicked(&self) -> bool {
+        self.bounds
+            .iter()
+            .any(|b| b.vertex_marks.iter().any(|m| m.is_clicked()))
+    }
+
+    pub fn loaded_arcs(&self) -> usize {
+        // whole arcs carrying a load in the active case, which chord-based exports get wrong
+        self.bounds
+            .iter()
+            .flat_map(|b| b.edges_and_marks())
+            .filter(|(e, m)| matches!(e, Edge::Arc(_)) && m.is_force())
+            .count()
+    }
+
+    pub fn clear_annotations(&mut self) -> usize {
+        self.bounds.iter_mut().map(|b| b.clear_annotations()).sum()
+    }
+
     pub fn bounding_box(&self) -> (Point, Point) {
-        // currently relies on the outer bound coming first (see below From<PartModel>)
-        self.0[0].bound.bounding_box()
+        // over every region
+        self.bounds
+            .iter()
+            .map(|b| b.bound.bounding_box())
+            .reduce(|(a_min, a_max), (b_min, b_max)| {
+                (
+                    Point::new(a_min.x.min(b_min.x), a_min.y.min(b_min.y)),
+                    Point::new(a_max.x.max(b_max.x), a_max.y.max(b_max.y)),
+                )
+            })
+            .unwrap()
+    }
+
+    pub fn regions(&self) -> &[RegionProps] {
+        &self.regions
+    }
+
+    pub fn assign_clicked_regions(
+        &mut self,
+        material: Option<String>,
+        thickness: Option<f64>,
+    ) -> Vec<usize> {
+        // set the properties of every region holding a selected edge, returning their indices
+        let mut hit: Vec<usize> = self
+            .bounds
+            .iter()
+            .filter(|b| b.any_clicked())
+            .map(|b| b.region)
+            .collect();
+        hit.sort();
+        hit.dedup();
+
+        for &r in hit.iter() {
+            self.regions[r] = RegionProps {
+                material: material.clone(),
+                thickness,
+            };
+        }
+
+        hit
+    }
+
+    pub fn cases(&self) -> &[String] {
+        &self.cases
+    }
+
+    pub fn active_case(&self) -> &str {
+        &self.cases[self.active]
+    }
+
+    pub fn body_loads(&self) -> BodyLoads {
+        self.body_loads[self.active]
+    }
+
+    pub fn body_loads_mut(&mut self) -> &mut BodyLoads {
+        &mut self.body_loads[self.active]
+    }
+
+    pub fn add_case(&mut self, name: &str) -> Result<usize, String> {
+        // a new case starts without loads and becomes the active one
+        if name.is_empty() {
+            return Err("load case needs a name".to_string());
+        }
+        if self.cases.iter().any(|c| c == name) {
+            return Err(format!("load case {} already exists", name));
+        }
+
+        self.cases.push(name.to_string());
+        self.body_loads.push(BodyLoads::default());
+        self.switch_case(name)
+    }
+
+    pub fn switch_case(&mut self, name: &str) -> Result<usize, String> {
+        // returns how many stashed loads were dropped for conflicting with the constraints
+        let to = self
+            .cases
+            .iter()
+            .position(|c| c == name)
+            .ok_or(format!("no load case {}", name))?;
+
+        let from = self.active;
+        self.active = to;
+
+        let mut dropped = 0;
+        for b in self.bounds.iter_mut() {
+            for m in b.marks.iter_mut().chain(b.vertex_marks.iter_mut()) {
+                dropped += m.switch_case(from, to);
+            }
+        }
+
+        Ok(dropped)
+    }
+
+    fn all_marks_mut(&mut self) -> impl Iterator<Item = &mut Mark> {
+        self.bounds
+            .iter_mut()
+            .flat_map(|b| b.marks.iter_mut().chain(b.vertex_marks.iter_mut()))
+    }
+
+    pub fn groups(&self) -> &[String] {
+        &self.groups
+    }
+
+    pub fn save_group(&mut self, name: &str) -> Result<usize, String> {
+        // name the current selection, replacing any group of the same name
+        // returns the number of edges and vertices in the group
+        // inp set names have to start with a letter
+        let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic()) || !name.chars().all(valid) {
+            return Err(format!("\"{}\" is not a valid group name", name));
+        }
+
+        // an empty selection leaves any existing group alone
+        let count = self
+            .bounds
+            .iter()
+            .flat_map(|b| b.marks.iter().chain(b.vertex_marks.iter()))
+            .filter(|m| m.is_clicked())
+            .count();
+        if count == 0 {
+            return Err(format!("nothing selected for group {}", name));
+        }
+
+        for m in self.all_marks_mut() {
+            m.groups.retain(|g| g != name);
+
+            if m.is_clicked() {
+                m.groups.push(name.to_string());
+            }
+        }
+
+        self.groups.retain(|g| g != name);
+        self.groups.push(name.to_string());
+
+        Ok(count)
+    }
+
+    pub fn select_group(&mut self, name: &str) -> Result<usize, String> {
+        // replace the selection with the group's members
+        if !self.groups.iter().any(|g| g == name) {
+            return Err(format!("no group {}", name));
+        }
+
+        self.clear_interactions();
+
+        let mut count = 0;
+        for m in self
+            .all_marks_mut()
+            .filter(|m| m.groups.iter().any(|g| g == name))
+        {
+            m.click();
+            count += 1;
+        }
+
+        Ok(count)
     }
 
     pub fn segmentify(&mut self, len: f64) {
         // convert boundaries to segments, cloning marks as appropriate
         // len is maximum segment length of subdivided curve
 
-        for b in self.0.iter_mut() {
+        for b in self.bounds.iter_mut() {
             *b = b.clone().segmentify(len); // boo clone
         }
     }
@@ -232,12 +1085,23 @@ impl MarkedModel {
 impl From<PartModel> for MarkedModel {
     fn from(model: PartModel) -> Self {
         let mut res = Vec::new();
+        let regions = vec![RegionProps::default(); model.regions.len()];
 
-        res.push(model.outer_bound.into());
-        for b in model.inner_bounds {
-            res.push(b.into())
+        for (i, r) in model.regions.into_iter().enumerate() {
+            for b in std::iter::once(r.outer_bound).chain(r.inner_bounds) {
+                let mut b = MarkedBound::from(b);
+                b.region = i;
+                res.push(b);
+            }
         }
 
-        Self(res)
+        Self {
+            bounds: res,
+            cases: vec![DEFAULT_CASE.to_string()],
+            active: 0,
+            body_loads: vec![BodyLoads::default()],
+            groups: Vec::new(),
+            regions,
+        }
     }
 }
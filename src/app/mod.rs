@@ -1,26 +1,52 @@
+pub mod body;
+mod force;
+mod history;
 pub mod mark;
-mod plot;
+pub mod plot;
+pub mod profile;
 
 use iced::widget::{button, column, pick_list, row, scrollable, text, text_input};
 use iced::{Alignment, Element, Length, Sandbox};
 
+use crate::export::dxf::write_dxf;
+use crate::export::inp::InpWriter;
+use crate::export::svg::write_svg;
+use crate::material::{Library, Material};
 use crate::reader::PartModel;
-use crate::writer::Writer;
-use mark::{Annotation, MarkedModel};
+use crate::writer::{Condition, Writer};
+use history::History;
+use mark::{Annotation, Feature, Mark, MarkedModel, CHAIN_TOLERANCE};
+use plot::SelectMode;
+use profile::Profile;
 
 #[derive(Default)]
 pub struct LacoApp {
     model: Option<MarkedModel>,
+    history: History,
     canvas_state: plot::CanvasState,
     log: String,
 
     selected_unit: Option<Unit>,
 
+    materials: Library,
+    selected_material: Option<String>,
+    condition: Condition,
+
+    // clearing every annotation waits for a second confirming press
+    confirm_clear: bool,
+
     // contents of text input boxes
     source_text: String,
     force_text: String,
+    force_error: String,
+    displacement_text: String,
+    spring_text: String,
+    case_text: String,
+    group_text: String,
+    gravity_text: String,
+    rotation_text: String,
+    profile_text: String,
     size_text: String,
-    material_text: String,
     thickness_text: String,
 }
 
@@ -37,18 +63,136 @@ pub enum Message {
     ConstrainTangent,
     ForceChanged(String),
     SetForce,
+    SetPressure,
+    SetTotalForce,
+    DisplacementChanged(String),
+    SetDisplacement,
+    SpringChanged(String),
+    SetSpring,
+    CaseChanged(String),
+    AddCase,
+    CaseSelected(String),
+    GroupChanged(String),
+    SaveGroup,
+    GroupSelected(String),
+    GravityChanged(String),
+    SetGravity,
+    RotationChanged(String),
+    SetRotation,
+    ProfileChanged(String),
+    SetProfile,
     Write,
+    ExportInp,
+    ExportSvg,
+    ExportDxf,
     SizeChanged(String),
     Segmentify,
-    MaterialChanged(String),
+    MaterialSelected(String),
+    ConditionSelected(Condition),
+    AssignRegion,
     ThicknessChanged(String),
+    Undo,
+    Redo,
+    SelectChain,
+    SelectBoundary,
+    Symmetry,
+    RemoveAnnotation,
+    ClearAnnotations,
+    ConfirmClearAnnotations,
+    CancelClearAnnotations,
+}
+
+impl LacoApp {
+    fn region_properties(
+        &self,
+        model: &MarkedModel,
+        scale: f64,
+    ) -> Result<Vec<(Material, f64)>, String> {
+        // each region's own material and thickness (scaled to meters), falling back to the
+        // ones in the write fields for anything not assigned
+        model
+            .regions()
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let name = r
+                    .material
+                    .as_ref()
+                    .or(self.selected_material.as_ref())
+                    .ok_or(format!("select a material for region {}", i + 1))?;
+                let material = self
+                    .materials
+                    .get(name)
+                    .ok_or(format!("{} is not in the material library", name))?;
+
+                // axisymmetric models don't use one
+                let thickness = match r.thickness {
+                    _ if !self.condition.needs_thickness() => 0.0,
+                    Some(t) => t,
+                    None => self
+                        .thickness_text
+                        .parse()
+                        .map_err(|_| format!("ill-formed thickness for region {}", i + 1))?,
+                };
+
+                Ok((material.clone(), thickness * scale))
+            })
+            .collect()
+    }
+
+    fn annotate(&mut self, annot: Annotation) -> usize {
+        self.annotate_with(|m| m.annotate_clicked(&annot))
+    }
+
+    fn annotate_with<F>(&mut self, f: F) -> usize
+    where
+        F: FnOnce(&mut MarkedModel) -> Vec<(Feature, Result<Mark, String>)>,
+    {
+        // apply an annotation to the selection as one undoable step
+        // nothing is recorded when every feature refused it (or none was selected)
+        // returns how many features took it
+        let before = self.model.clone();
+        let mut applied = 0;
+
+        if let Some(m) = self.model.as_mut() {
+            let marked = f(m);
+            m.clear_interactions();
+
+            applied = marked.iter().filter(|(_, res)| res.is_ok()).count();
+            if applied > 0 {
+                self.history.record(&before);
+            }
+
+            for (feature, res) in marked {
+                match res {
+                    Ok(mark) => self
+                        .log
+                        .push_str(&format!("annotated {:?} with {:?}\n", feature, mark)),
+                    Err(e) => self
+                        .log
+                        .push_str(&format!("could not annotate {:?}: {}\n", feature, e)),
+                }
+            }
+        }
+
+        self.canvas_state.request_redraw();
+
+        applied
+    }
 }
 
 impl Sandbox for LacoApp {
     type Message = Message;
 
     fn new() -> LacoApp {
-        LacoApp::default()
+        let materials = Library::load();
+        let log = format!("{} materials in the library\n", materials.names().len());
+
+        LacoApp {
+            materials,
+            log,
+            ..LacoApp::default()
+        }
     }
 
     fn title(&self) -> String {
@@ -61,17 +205,25 @@ impl Sandbox for LacoApp {
                 self.source_text = s;
             }
             Message::LoadModel => {
-                self.model = Some(PartModel::load_dxf(&self.source_text).into());
+                self.history.record(&self.model);
+                self.model = Some(PartModel::load(&self.source_text).into());
                 self.canvas_state.request_redraw();
 
                 self.source_text.clear();
 
-                self.log.push_str("loaded model\n"); // capture stderr from load?
+                // capture stderr from load?
+                let regions = self.model.as_ref().map_or(0, |m| m.regions().len());
+                self.log
+                    .push_str(&format!("loaded model with {} regions\n", regions));
             }
             Message::UnitSelected(u) => {
                 self.selected_unit = Some(u);
             }
             Message::Clear => {
+                // clearing nothing isn't worth an undo step
+                if self.model.is_some() {
+                    self.history.record(&self.model);
+                }
                 self.model = None;
                 self.canvas_state = plot::CanvasState::default();
             }
@@ -79,16 +231,62 @@ impl Sandbox for LacoApp {
                 plot::PlotMessage::Redraw => {
                     self.canvas_state.request_redraw();
                 }
-                plot::PlotMessage::Select(p) => {
+                plot::PlotMessage::Select(p, r, mode) => {
                     self.model.as_mut().map(|m| {
-                        m.clear_interactions();
+                        if mode == SelectMode::Replace {
+                            m.clear_interactions();
+                        }
+
+                        // vertices near the click take priority over the edge under it
+                        let vertex = match mode {
+                            SelectMode::Toggle => m.toggle_vertex_at_pos(p, r),
+                            _ => m.click_vertex_at_pos(p, r),
+                        };
+
+                        if let Some((vertex, mark)) = vertex {
+                            self.log.push_str(&format!(
+                                "selected vertex {:?} with annotation {:?}\n",
+                                vertex, mark
+                            ));
+                            return;
+                        }
+
+                        let (edge, mark) = match mode {
+                            SelectMode::Toggle => m.toggle_at_pos(p),
+                            _ => m.click_at_pos(p),
+                        };
 
-                        let (edge, mark) = m.click_at_pos(p);
                         self.log
                             .push_str(&format!("selected {:?} with annotation {:?}\n", edge, mark));
                     });
                     self.canvas_state.request_redraw();
                 }
+                plot::PlotMessage::SelectChain(p, mode) => {
+                    self.update(Message::Plot(plot::PlotMessage::Select(p, 0.0, mode)));
+                    self.update(Message::SelectChain);
+                }
+                plot::PlotMessage::SelectBoundary(p, mode) => {
+                    self.update(Message::Plot(plot::PlotMessage::Select(p, 0.0, mode)));
+                    self.update(Message::SelectBoundary);
+                }
+                plot::PlotMessage::SelectBox(a, b, mode) => {
+                    self.model.as_mut().map(|m| {
+                        if mode == SelectMode::Replace {
+                            m.clear_interactions();
+                        }
+
+                        let selected = m.click_in_box(a, b);
+                        self.log
+                            .push_str(&format!("selected {} edges in box\n", selected.len()));
+                    });
+                    self.canvas_state.request_redraw();
+                }
+                plot::PlotMessage::Undo => {
+                    self.update(Message::Undo);
+                }
+                plot::PlotMessage::Redo => {
+                    self.update(Message::Redo);
+                }
                 plot::PlotMessage::Deselect => {
                     self.model.as_mut().map(|m| {
                         m.clear_interactions();
@@ -98,105 +296,367 @@ impl Sandbox for LacoApp {
                 }
             },
             Message::ConstrainX => {
-                self.model.as_mut().map(|m| {
-                    let marked = m.annotate_clicked(Annotation::ConstrainX);
-                    m.clear_interactions();
+                self.annotate(Annotation::ConstrainX);
+            }
+            Message::ConstrainY => {
+                self.annotate(Annotation::ConstrainY);
+            }
+            Message::ConstrainXY => {
+                self.annotate(Annotation::ConstrainXY);
+            }
+            Message::ConstrainTangent => {
+                self.annotate(Annotation::ConstrainTangent);
+            }
+            Message::ForceChanged(f) => {
+                self.force_text = f;
+                self.force_error.clear();
+            }
+            Message::SetForce => match Annotation::parse_force(&self.force_text) {
+                Ok(annot) => {
+                    self.annotate(annot);
+                    self.force_text.clear();
+                }
+                // the text stays so it can be fixed
+                Err(e) => self.force_error = e,
+            },
+            Message::SetPressure => match Annotation::parse_pressure(&self.force_text) {
+                Ok(annot) => {
+                    self.annotate(annot);
+                    self.force_text.clear();
+                }
+                Err(e) => self.force_error = e,
+            },
+            Message::SetTotalForce => {
+                let scale = self.selected_unit.map(|u| u.scale()).unwrap_or(1.0);
+                let len = self.model.as_ref().map_or(0.0, |m| m.clicked_length()) * scale;
+                let vertices = self
+                    .model
+                    .as_ref()
+                    .map_or(false, |m| m.any_vertex_clicked());
 
-                    for (edge, mark) in marked {
-                        self.log
-                            .push_str(&format!("annotated {:?} with {:?}\n", edge, mark));
+                match Annotation::parse_force(&self.force_text) {
+                    // a traction means nothing at a vertex, and the total is over edges only
+                    Ok(Annotation::Force(_, _)) if vertices => self
+                        .log
+                        .push_str("deselect the vertices to spread a total force\n"),
+                    Ok(Annotation::Force(x, y)) if len > 0.0 => {
+                        // an edge refusing its share would leave less than the total applied,
+                        // so try the force on a copy first
+                        let refused = self.model.clone().map_or(0, |mut m| {
+                            m.annotate_clicked(&Annotation::Force(x, y))
+                                .iter()
+                                .filter(|(_, r)| r.is_err())
+                                .count()
+                        });
+
+                        if refused > 0 {
+                            self.log.push_str(&format!(
+                                "{} selected edges refuse the force, total not applied\n",
+                                refused
+                            ));
+                        } else {
+                            // spread the total evenly, as a traction per meter of edge
+                            let (tx, ty) = (x / len, y / len);
+                            self.log.push_str(&format!(
+                                "total force ({}, {}) over {:.5} m is a traction of ({:.5}, {:.5})\n",
+                                x, y, len, tx, ty
+                            ));
+
+                            self.annotate(Annotation::Force(tx, ty));
+                        }
                     }
-                });
+                    Ok(Annotation::Force(_, _)) => {
+                        self.log.push_str("no edges selected for total force\n")
+                    }
+                    Ok(_) => self.force_error = "a total force needs a direction".to_string(),
+                    Err(e) => self.force_error = e,
+                }
 
-                self.canvas_state.request_redraw();
+                if self.force_error.is_empty() {
+                    self.force_text.clear();
+                }
             }
-            Message::ConstrainY => {
-                self.model.as_mut().map(|m| {
-                    let marked = m.annotate_clicked(Annotation::ConstrainY);
-                    m.clear_interactions();
+            Message::DisplacementChanged(d) => {
+                self.displacement_text = d;
+            }
+            Message::SetDisplacement => {
+                // given in the model's units, scaled to meters on write like the geometry
+                if let Some(annot) = Annotation::parse_displacement(&self.displacement_text) {
+                    self.annotate(annot);
+                } else {
+                    self.log.push_str("ill-formed displacement text\n");
+                }
 
-                    for (edge, mark) in marked {
-                        self.log
-                            .push_str(&format!("annotated {:?} with {:?}\n", edge, mark));
-                    }
-                });
+                self.displacement_text.clear();
+            }
+            Message::SpringChanged(k) => {
+                self.spring_text = k;
+            }
+            Message::SetSpring => {
+                if let Some(annot) = Annotation::parse_spring(&self.spring_text) {
+                    self.annotate(annot);
+                } else {
+                    self.log.push_str("ill-formed spring text\n");
+                }
 
-                self.canvas_state.request_redraw();
+                self.spring_text.clear();
             }
-            Message::ConstrainXY => {
-                self.model.as_mut().map(|m| {
-                    let marked = m.annotate_clicked(Annotation::ConstrainXY);
-                    m.clear_interactions();
+            Message::CaseChanged(c) => {
+                self.case_text = c;
+            }
+            Message::AddCase => {
+                if let Some(m) = self.model.as_mut() {
+                    let before = Some(m.clone());
 
-                    for (edge, mark) in marked {
-                        self.log
-                            .push_str(&format!("annotated {:?} with {:?}\n", edge, mark));
+                    match m.add_case(self.case_text.trim()) {
+                        Ok(_) => {
+                            self.history.record(&before);
+                            let c = m.active_case();
+                            self.log.push_str(&format!("added load case {}\n", c));
+                        }
+                        Err(e) => self.log.push_str(&format!("{}\n", e)),
                     }
-                });
+                }
 
+                self.case_text.clear();
                 self.canvas_state.request_redraw();
             }
-            Message::ConstrainTangent => {
-                self.model.as_mut().map(|m| {
-                    let marked = m.annotate_clicked(Annotation::ConstrainTangent);
-                    m.clear_interactions();
+            Message::CaseSelected(c) => {
+                if let Some(m) = self.model.as_mut().filter(|m| m.active_case() != c) {
+                    let before = Some(m.clone());
 
-                    for (edge, mark) in marked {
-                        self.log
-                            .push_str(&format!("annotated {:?} with {:?}\n", edge, mark));
+                    match m.switch_case(&c) {
+                        Ok(n) => {
+                            self.history.record(&before);
+                            self.log.push_str(&format!("switched to load case {}\n", c));
+                            if n > 0 {
+                                self.log
+                                    .push_str(&format!("dropped {} conflicting loads\n", n));
+                            }
+                        }
+                        Err(e) => self.log.push_str(&format!("{}\n", e)),
                     }
-                });
+                }
 
                 self.canvas_state.request_redraw();
             }
-            Message::ForceChanged(f) => {
-                self.force_text = f;
+            Message::GroupChanged(g) => {
+                self.group_text = g;
             }
-            Message::SetForce => {
-                self.model.as_mut().map(|m| {
-                    if let Some(annot) = Annotation::parse_force(&self.force_text) {
-                        let marked = m.annotate_clicked(annot);
-
-                        m.clear_interactions();
+            Message::SaveGroup => {
+                if let Some(m) = self.model.as_mut() {
+                    let before = Some(m.clone());
 
-                        for (edge, mark) in marked {
+                    let name = self.group_text.trim();
+                    match m.save_group(name) {
+                        Ok(n) => {
+                            self.history.record(&before);
                             self.log
-                                .push_str(&format!("annotated {:?} with {:?}\n", edge, mark));
+                                .push_str(&format!("saved {} items as {}\n", n, name))
                         }
-                    } else {
-                        self.log.push_str("ill-formed force text\n");
+                        Err(e) => self.log.push_str(&format!("{}\n", e)),
                     }
-                });
+                }
 
-                self.force_text.clear();
+                self.group_text.clear();
+            }
+            Message::GroupSelected(g) => {
+                if let Some(m) = self.model.as_mut() {
+                    match m.select_group(&g) {
+                        Ok(n) => self
+                            .log
+                            .push_str(&format!("selected {} items of {}\n", n, g)),
+                        Err(e) => self.log.push_str(&format!("{}\n", e)),
+                    }
+                }
 
                 self.canvas_state.request_redraw();
             }
+            Message::GravityChanged(g) => {
+                self.gravity_text = g;
+            }
+            Message::SetGravity => {
+                // an empty field removes the load
+                let gravity = match self.gravity_text.trim() {
+                    "" => Ok(None),
+                    g => body::parse_gravity(g).map(Some),
+                };
+
+                match (gravity, self.model.as_mut()) {
+                    (Ok(g), Some(m)) => {
+                        self.history.record(&Some(m.clone()));
+                        m.body_loads_mut().gravity = g;
+
+                        self.log.push_str(&format!(
+                            "gravity {:?} m/s^2 in load case {}\n",
+                            g,
+                            m.active_case()
+                        ));
+                        self.gravity_text.clear();
+                    }
+                    (Err(e), _) => self.log.push_str(&format!("ill-formed gravity: {}\n", e)),
+                    (_, None) => (),
+                }
+            }
+            Message::RotationChanged(r) => {
+                self.rotation_text = r;
+            }
+            Message::SetRotation => {
+                let rotation = match self.rotation_text.trim() {
+                    "" => Ok(None),
+                    r => body::parse_rotation(r).map(Some),
+                };
+
+                match (rotation, self.model.as_mut()) {
+                    (Ok(r), Some(m)) => {
+                        self.history.record(&Some(m.clone()));
+                        m.body_loads_mut().rotation = r;
+
+                        self.log.push_str(&format!(
+                            "rotation {:?} rad/s in load case {}\n",
+                            r,
+                            m.active_case()
+                        ));
+                        self.rotation_text.clear();
+                    }
+                    (Err(e), _) => self.log.push_str(&format!("ill-formed rotation: {}\n", e)),
+                    (_, None) => (),
+                }
+            }
+            Message::ProfileChanged(p) => {
+                self.profile_text = p;
+            }
+            Message::SetProfile => match Profile::parse(&self.profile_text) {
+                Ok(profile) => {
+                    self.annotate_with(|m| m.annotate_clicked_profile(profile));
+                    self.profile_text.clear();
+                }
+                Err(e) => {
+                    self.log.push_str(&format!("ill-formed profile: {}\n", e));
+                }
+            },
             Message::Write => {
                 // TODO: set scale with a units radio button
                 let scale = self.selected_unit.map(|u| u.scale()).unwrap_or(1.0);
 
-                let mut writer = Writer::new().scale(scale);
+                if let Some(model) = self.model.as_ref() {
+                    match self.region_properties(model, scale) {
+                        Err(e) => self.log.push_str(&format!("{}\n", e)),
+                        Ok(_) if self.condition == Condition::Axisymmetric && model.off_axis() => {
+                            self.log
+                                .push_str("axisymmetric models must lie at x >= 0\n")
+                        }
+                        Ok(regions) => {
+                            // bugi takes one loading per file, so every load case gets its own
+                            for case in model.cases() {
+                                let mut m = model.clone();
+                                let dropped = m.switch_case(case).unwrap();
+                                if dropped > 0 {
+                                    self.log.push_str(&format!(
+                                        "{} conflicting loads left out of {}\n",
+                                        dropped, case
+                                    ));
+                                }
+
+                                let mut writer =
+                                    Writer::new().scale(scale).body_loads(m.body_loads());
+                                for b in m.bounds().cloned() {
+                                    writer.add_boundary(b);
+                                }
+
+                                let path = if model.cases().len() == 1 {
+                                    "out.bbnd".to_string()
+                                } else {
+                                    format!("out_{}.bbnd", file_safe(case))
+                                };
+                                writer.write(&path, self.condition, &regions);
 
-                self.model.as_ref().map(|m| {
-                    for b in m.bounds().cloned() {
+                                self.log.push_str(&format!("wrote {}\n", path));
+                            }
+                        }
+                    }
+                }
+
+                self.thickness_text.clear();
+            }
+            Message::ExportInp => {
+                let scale = self.selected_unit.map(|u| u.scale()).unwrap_or(1.0);
+
+                if let Some(model) = self.model.as_ref() {
+                    // loads are lumped over the chord, which is wrong for a whole arc
+                    let arcs = model.loaded_arcs();
+                    if arcs > 0 {
+                        self.log.push_str(&format!(
+                            "segmentify the {} loaded arcs before exporting the inp\n",
+                            arcs
+                        ));
+                        return;
+                    }
+
+                    let mut writer = InpWriter::new().scale(scale).body_loads(model.body_loads());
+
+                    for b in model.bounds().cloned() {
                         writer.add_boundary(b);
                     }
-                });
 
-                if let Ok(thickness) = self.thickness_text.parse::<f64>() {
-                    let thickness = thickness * scale;
-                    writer.write("out.bbnd", &self.material_text, thickness);
+                    match self.region_properties(model, scale) {
+                        Ok(regions) => {
+                            writer.write("out.inp", self.condition, &regions);
+                            self.log.push_str("exported out.inp\n");
+                        }
+                        Err(e) => self.log.push_str(&format!("{}\n", e)),
+                    }
                 }
+            }
+            Message::AssignRegion => {
+                let material = self.selected_material.clone();
+                let thickness = self.thickness_text.parse::<f64>().ok();
 
-                self.material_text.clear();
-                self.thickness_text.clear();
+                if let Some(m) = self.model.as_mut() {
+                    let before = Some(m.clone());
+
+                    let hit = m.assign_clicked_regions(material.clone(), thickness);
+                    if hit.is_empty() {
+                        self.log
+                            .push_str("select an edge of each region to assign\n");
+                    } else {
+                        self.history.record(&before);
+                    }
+                    for r in hit {
+                        self.log.push_str(&format!(
+                            "region {}: material {}, thickness {}\n",
+                            r + 1,
+                            material.as_deref().unwrap_or("(default)"),
+                            thickness.map_or("(default)".to_string(), |t| t.to_string())
+                        ));
+                    }
+                }
+            }
+            Message::ExportSvg => {
+                if let Some(m) = self.model.as_ref() {
+                    write_svg(m, "out.svg");
+                    self.log.push_str("exported out.svg\n");
+                }
+            }
+            Message::ExportDxf => {
+                if let Some(m) = self.model.as_ref() {
+                    // an unpicked unit stays unitless rather than claiming meters
+                    let units = self
+                        .selected_unit
+                        .map_or(dxf::enums::Units::Unitless, |u| u.dxf_units());
+                    write_dxf(m, "out.dxf", units);
+                    self.log.push_str("exported out.dxf\n");
+                }
             }
             Message::SizeChanged(s) => {
                 self.size_text = s;
             }
             Message::Segmentify => {
                 if let Ok(s) = self.size_text.parse() {
+                    if self.model.is_some() {
+                        self.history.record(&self.model);
+                    }
+
                     self.model.as_mut().map(|m| {
                         // TODO user-specified precision
                         m.segmentify(s);
@@ -211,12 +671,112 @@ impl Sandbox for LacoApp {
 
                 self.canvas_state.request_redraw();
             }
-            Message::MaterialChanged(m) => {
-                self.material_text = m;
+            Message::MaterialSelected(m) => {
+                self.selected_material = Some(m);
+            }
+            Message::ConditionSelected(c) => {
+                self.condition = c;
+
+                // axisymmetric models need the axis at x = 0 held radially
+                if let (Condition::Axisymmetric, Some(m)) = (c, self.model.as_mut()) {
+                    if m.off_axis() {
+                        self.log
+                            .push_str("axisymmetric models must lie at x >= 0\n");
+                    } else {
+                        match m.click_axis() {
+                            0 => self.log.push_str("no edges lie on the axis\n"),
+                            n => self.log.push_str(&format!(
+                                "selected {} edges on the axis, constrain them with X\n",
+                                n
+                            )),
+                        }
+                    }
+
+                    self.canvas_state.request_redraw();
+                }
             }
             Message::ThicknessChanged(t) => {
                 self.thickness_text = t;
             }
+            Message::SelectChain => {
+                self.model.as_mut().map(|m| {
+                    let selected = m.click_chains(CHAIN_TOLERANCE);
+                    self.log
+                        .push_str(&format!("extended selection by {} edges\n", selected.len()));
+                });
+                self.canvas_state.request_redraw();
+            }
+            Message::SelectBoundary => {
+                self.model.as_mut().map(|m| {
+                    let selected = m.click_boundaries();
+                    self.log
+                        .push_str(&format!("selected {} boundary edges\n", selected.len()));
+                });
+                self.canvas_state.request_redraw();
+            }
+            Message::Symmetry => {
+                let found = self.model.as_mut().and_then(|m| m.click_symmetry_line());
+
+                match found {
+                    Some((line, count)) => {
+                        // some edges on the line may refuse the constraint, so count after
+                        let annot = Annotation::symmetry(&line);
+                        let applied = self.annotate(annot.clone());
+                        self.log.push_str(&format!(
+                            "symmetry line through {:?} and {:?}: {} of {} edges got {:?}\n",
+                            line.p(),
+                            line.q(),
+                            applied,
+                            count,
+                            annot
+                        ));
+                    }
+                    None => self
+                        .log
+                        .push_str("select a straight edge on the symmetry line first\n"),
+                }
+            }
+            Message::RemoveAnnotation => {
+                self.annotate(Annotation::Empty);
+            }
+            Message::ClearAnnotations => {
+                if self.model.is_some() {
+                    self.confirm_clear = true;
+                }
+            }
+            Message::ConfirmClearAnnotations => {
+                self.confirm_clear = false;
+
+                if self.model.is_some() {
+                    self.history.record(&self.model);
+                }
+
+                self.model.as_mut().map(|m| {
+                    let count = m.clear_annotations();
+                    self.log
+                        .push_str(&format!("cleared annotations from {} edges\n", count));
+                });
+                self.canvas_state.request_redraw();
+            }
+            Message::CancelClearAnnotations => {
+                self.confirm_clear = false;
+            }
+            Message::Undo => {
+                if self.history.undo(&mut self.model) {
+                    self.log.push_str("undid last change\n");
+                } else {
+                    self.log.push_str("nothing to undo\n");
+                }
+                self.canvas_state.request_redraw();
+            }
+            Message::Redo => {
+                if self.history.redo(&mut self.model) {
+                    self.log.push_str("redid last change\n");
+                } else {
+                    self.log.push_str("nothing to redo\n");
+                }
+                self.canvas_state.request_redraw();
+            }
         }
     }
 
@@ -244,6 +804,32 @@ impl Sandbox for LacoApp {
         ]
         .spacing(10);
 
+        let select_field = row![
+            text("Select: "),
+            button("Chain").padding(8).on_press(Message::SelectChain),
+            button("Boundary")
+                .padding(8)
+                .on_press(Message::SelectBoundary),
+        ]
+        .spacing(10);
+
+        let group_field = row![
+            text("Group: "),
+            pick_list(
+                self.model
+                    .as_ref()
+                    .map_or(Vec::new(), |m| m.groups().to_vec()),
+                None::<String>,
+                Message::GroupSelected
+            )
+            .placeholder("select"),
+            text_input("group name", &self.group_text)
+                .on_input(Message::GroupChanged)
+                .padding(8),
+            button("Save").padding(8).on_press(Message::SaveGroup)
+        ]
+        .spacing(10);
+
         let constraint_field = row![
             text("Constrain: "),
             button("X").padding(8).on_press(Message::ConstrainX),
@@ -252,14 +838,104 @@ impl Sandbox for LacoApp {
             button("Tangent")
                 .padding(8)
                 .on_press(Message::ConstrainTangent),
+            button("Symmetry").padding(8).on_press(Message::Symmetry),
         ]
         .spacing(10);
 
+        let remove_field = if self.confirm_clear {
+            row![
+                text("Clear all annotations? "),
+                button("Confirm")
+                    .padding(8)
+                    .on_press(Message::ConfirmClearAnnotations),
+                button("Cancel")
+                    .padding(8)
+                    .on_press(Message::CancelClearAnnotations),
+            ]
+        } else {
+            row![
+                text("Annotation: "),
+                button("Remove")
+                    .padding(8)
+                    .on_press(Message::RemoveAnnotation),
+                button("Clear all")
+                    .padding(8)
+                    .on_press(Message::ClearAnnotations),
+            ]
+        }
+        .spacing(10);
+
         let force_field = row![
-            text_input("force value", &self.force_text)
-                .on_input(Message::ForceChanged)
+            text_input(
+                "force: x, y or 500 N @ 30deg or 2 kN/m normal",
+                &self.force_text
+            )
+            .on_input(Message::ForceChanged)
+            .padding(8),
+            button("Set").padding(8).on_press(Message::SetForce),
+            button("Total").padding(8).on_press(Message::SetTotalForce),
+            button("Pressure").padding(8).on_press(Message::SetPressure),
+            text(&self.force_error)
+        ]
+        .spacing(10);
+
+        let displacement_field = row![
+            text_input(
+                "displacement: dx, dy (_ leaves free)",
+                &self.displacement_text
+            )
+            .on_input(Message::DisplacementChanged)
+            .padding(8),
+            button("Displace")
+                .padding(8)
+                .on_press(Message::SetDisplacement)
+        ]
+        .spacing(10);
+
+        let spring_field = row![
+            text_input("spring stiffness per length: kn, kt", &self.spring_text)
+                .on_input(Message::SpringChanged)
+                .padding(8),
+            button("Spring").padding(8).on_press(Message::SetSpring)
+        ]
+        .spacing(10);
+
+        let case_field = row![
+            text("Load case: "),
+            pick_list(
+                self.model
+                    .as_ref()
+                    .map_or(Vec::new(), |m| m.cases().to_vec()),
+                self.model.as_ref().map(|m| m.active_case().to_string()),
+                Message::CaseSelected
+            ),
+            text_input("new load case", &self.case_text)
+                .on_input(Message::CaseChanged)
+                .padding(8),
+            button("Add").padding(8).on_press(Message::AddCase)
+        ]
+        .spacing(10);
+
+        let body_field = row![
+            text_input("gravity: gx, gy (m/s^2)", &self.gravity_text)
+                .on_input(Message::GravityChanged)
                 .padding(8),
-            button("Set").padding(8).on_press(Message::SetForce)
+            button("Gravity").padding(8).on_press(Message::SetGravity),
+            text_input("rotation: rad/s or rpm @ cx, cy", &self.rotation_text)
+                .on_input(Message::RotationChanged)
+                .padding(8),
+            button("Rotation").padding(8).on_press(Message::SetRotation)
+        ]
+        .spacing(10);
+
+        let profile_field = row![
+            text_input(
+                "profile: (fx, fy) -> (fx, fy) or fx ; fy of x, y, s, t",
+                &self.profile_text
+            )
+            .on_input(Message::ProfileChanged)
+            .padding(8),
+            button("Set").padding(8).on_press(Message::SetProfile)
         ]
         .spacing(10);
 
@@ -274,18 +950,32 @@ impl Sandbox for LacoApp {
         .spacing(10);
 
         let write_field = row![
-            text_input("material", &self.material_text)
-                .on_input(Message::MaterialChanged)
-                .padding(8),
+            pick_list(
+                self.materials.names(),
+                self.selected_material.clone(),
+                Message::MaterialSelected
+            )
+            .placeholder("material"),
+            pick_list(
+                &Condition::ALL[..],
+                Some(self.condition),
+                Message::ConditionSelected
+            ),
             text_input("thickness", &self.thickness_text)
                 .on_input(Message::ThicknessChanged)
                 .padding(8),
-            button("Write").padding(8).on_press(Message::Write)
+            button("Region").padding(8).on_press(Message::AssignRegion),
+            button("Write").padding(8).on_press(Message::Write),
+            button(".inp").padding(8).on_press(Message::ExportInp),
+            button(".svg").padding(8).on_press(Message::ExportSvg),
+            button(".dxf").padding(8).on_press(Message::ExportDxf)
         ]
         .spacing(10);
 
         let misc_field = row![
             button("Clear").padding(8).on_press(Message::Clear),
+            button("Undo").padding(8).on_press(Message::Undo),
+            button("Redo").padding(8).on_press(Message::Redo),
             pick_list(&Unit::ALL[..], self.selected_unit, Message::UnitSelected)
                 .placeholder("unit")
         ]
@@ -294,8 +984,16 @@ impl Sandbox for LacoApp {
         let control_pane = column![
             load_field,
             misc_field,
+            select_field,
+            group_field,
             constraint_field,
+            remove_field,
+            case_field,
+            displacement_field,
+            spring_field,
             force_field,
+            profile_field,
+            body_field,
             segment_field,
             write_field,
         ]
@@ -321,7 +1019,7 @@ pub enum Unit {
 }
 
 impl Unit {
-    const ALL: [Unit; 3] = [Unit::Meter, Unit::Millimeter, Unit::Inch];
+    pub const ALL: [Unit; 3] = [Unit::Meter, Unit::Millimeter, Unit::Inch];
 
     fn scale(self) -> f64 {
         // the scale by which to multiply a value in these units to obtain a value in meters
@@ -331,6 +1029,14 @@ impl Unit {
             Unit::Millimeter => 1.0 / 1000.0,
         }
     }
+
+    pub fn dxf_units(self) -> dxf::enums::Units {
+        match self {
+            Unit::Meter => dxf::enums::Units::Meters,
+            Unit::Millimeter => dxf::enums::Units::Millimeters,
+            Unit::Inch => dxf::enums::Units::Inches,
+        }
+    }
 }
 
 impl std::fmt::Display for Unit {
@@ -346,3 +1052,16 @@ impl std::fmt::Display for Unit {
         )
     }
 }
+
+fn file_safe(name: &str) -> String {
+    // load case names end up in file names
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
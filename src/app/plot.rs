@@ -7,7 +7,44 @@ use iced::{Element, Length, Rectangle, Renderer, Theme};
 
 use spacemath::two::boundary::Edge;
 
-use super::mark::{MarkedBound, MarkedModel};
+use super::mark::{Annotation, Mark, MarkedBound, MarkedModel};
+use crate::geom::{edge_mid, outward_normal};
+
+// edge colours, shared with the svg and dxf exporters
+pub const BLANK_RGB: [u8; 3] = [0, 0, 0];
+pub const CLICKED_RGB: [u8; 3] = [0, 150, 250];
+pub const FORCE_RGB: [u8; 3] = [250, 150, 0];
+pub const CONSTRAINT_RGB: [u8; 3] = [0, 250, 0];
+pub const COMBINED_RGB: [u8; 3] = [150, 0, 250];
+pub const SPRING_RGB: [u8; 3] = [160, 100, 40];
+
+pub fn mark_rgb(mark: &Mark) -> [u8; 3] {
+    // the selection shows over everything else
+    if mark.is_clicked() {
+        CLICKED_RGB
+    } else {
+        annotation_rgb(mark)
+    }
+}
+
+pub fn annotation_rgb(mark: &Mark) -> [u8; 3] {
+    // the colour of what the edge or vertex carries, whether or not it is selected
+    if mark.is_force() && (mark.is_constraint() || mark.is_spring()) {
+        COMBINED_RGB
+    } else if mark.is_force() {
+        FORCE_RGB
+    } else if mark.is_constraint() {
+        CONSTRAINT_RGB
+    } else if mark.is_spring() {
+        SPRING_RGB
+    } else {
+        BLANK_RGB
+    }
+}
+
+fn rgb(c: [u8; 3]) -> Color {
+    Color::from_rgb8(c[0], c[1], c[2])
+}
 
 #[derive(Default)]
 pub struct CanvasState {
@@ -40,16 +77,58 @@ struct Plot<'a> {
     model: Option<&'a MarkedModel>,
 }
 
+// load arrow sizes in px
+const ARROW_LEN: f32 = 20.0;
+const ARROW_HEAD: f32 = 6.0;
+const VERTEX_RADIUS: f32 = 4.0;
+
+// clicks within this distance (in px) of a vertex pick the vertex, less on short edges
+const VERTEX_PICK: f64 = 6.0;
+
+// drags shorter than this (in px) count as clicks
+const DRAG_THRESHOLD: f32 = 4.0;
+
+// two clicks closer together than this make a double click
+const DOUBLE_CLICK: std::time::Duration = std::time::Duration::from_millis(400);
+
 #[derive(Default, Clone, Debug)]
 struct PlotState {
     transform: Transform,
+    modifiers: keyboard::Modifiers,
+    drag_start: Option<iced::Point>,
+    last_click: Option<(std::time::Instant, iced::Point)>,
+}
+
+impl PlotState {
+    fn select_mode(&self) -> SelectMode {
+        if self.modifiers.shift() {
+            SelectMode::Add
+        } else if self.modifiers.command() {
+            SelectMode::Toggle
+        } else {
+            SelectMode::Replace
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectMode {
+    Replace,
+    Add,
+    Toggle,
 }
 
 #[derive(Clone, Debug)]
 pub enum PlotMessage {
     Redraw,
-    Select(spacemath::two::Point),
+    // point and vertex pick radius, both in model units
+    Select(spacemath::two::Point, f64, SelectMode),
+    SelectBox(spacemath::two::Point, spacemath::two::Point, SelectMode),
+    SelectChain(spacemath::two::Point, SelectMode),
+    SelectBoundary(spacemath::two::Point, SelectMode),
     Deselect,
+    Undo,
+    Redo,
 }
 
 impl<'a> canvas::Program<PlotMessage> for Plot<'a> {
@@ -61,7 +140,7 @@ impl<'a> canvas::Program<PlotMessage> for Plot<'a> {
         renderer: &Renderer,
         _theme: &Theme,
         bounds: Rectangle,
-        _cursor: mouse::Cursor,
+        cursor: mouse::Cursor,
     ) -> Vec<Geometry> {
         let content = self
             .canvas_state
@@ -74,7 +153,23 @@ impl<'a> canvas::Program<PlotMessage> for Plot<'a> {
                 );
             });
 
-        vec![content]
+        // the selection box changes with every cursor move, so it isn't cached
+        let mut overlay = Frame::new(renderer, bounds.size());
+        if let (Some(start), Some(end)) = (state.drag_start, cursor.position_in(bounds)) {
+            if start.distance(end) > DRAG_THRESHOLD {
+                let top_left = iced::Point::new(start.x.min(end.x), start.y.min(end.y));
+                let size = iced::Size::new((end.x - start.x).abs(), (end.y - start.y).abs());
+
+                overlay.stroke(
+                    &Path::rectangle(top_left, size),
+                    Stroke::default()
+                        .with_width(1.0)
+                        .with_color(rgb(CLICKED_RGB)),
+                );
+            }
+        }
+
+        vec![content, overlay.into_geometry()]
     }
 
     fn update(
@@ -84,6 +179,35 @@ impl<'a> canvas::Program<PlotMessage> for Plot<'a> {
         bounds: Rectangle,
         cursor: mouse::Cursor,
     ) -> (event::Status, Option<PlotMessage>) {
+        // track modifiers wherever the cursor is, they decide how clicks select
+        if let Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) = event {
+            state.modifiers = modifiers;
+            return (event::Status::Ignored, None);
+        }
+
+        // every release ends a drag, wherever the cursor is, so a release off the canvas
+        // can't leave a stale start for the next click
+        let drag_start = match event {
+            Event::Mouse(mouse::Event::ButtonReleased(_)) => state.drag_start.take(),
+            _ => state.drag_start,
+        };
+
+        // undo and redo act on the model, so they work wherever the cursor is
+        if let Event::Keyboard(keyboard::Event::KeyPressed {
+            key_code: keyboard::KeyCode::Z,
+            modifiers,
+        }) = event
+        {
+            if modifiers.command() {
+                let message = if modifiers.shift() {
+                    PlotMessage::Redo
+                } else {
+                    PlotMessage::Undo
+                };
+                return (event::Status::Captured, Some(message));
+            }
+        }
+
         let cursor_position = if let Some(position) = cursor.position_in(bounds) {
             position
         } else {
@@ -93,9 +217,45 @@ impl<'a> canvas::Program<PlotMessage> for Plot<'a> {
         match event {
             Event::Mouse(mouse_event) => {
                 let message = match mouse_event {
-                    mouse::Event::ButtonPressed(mouse::Button::Left) => Some(PlotMessage::Select(
-                        state.transform.reverse(cursor_position),
-                    )),
+                    mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                        state.drag_start = Some(cursor_position);
+                        None
+                    }
+                    mouse::Event::ButtonReleased(mouse::Button::Left) => {
+                        // a press off the canvas leaves nothing to finish
+                        drag_start.map(|start| {
+                            let mode = state.select_mode();
+                            let end = state.transform.reverse(cursor_position);
+
+                            if start.distance(cursor_position) > DRAG_THRESHOLD {
+                                return PlotMessage::SelectBox(
+                                    state.transform.reverse(start),
+                                    end,
+                                    mode,
+                                );
+                            }
+
+                            let now = std::time::Instant::now();
+                            let double = state.last_click.map_or(false, |(t, p)| {
+                                now - t < DOUBLE_CLICK
+                                    && p.distance(cursor_position) < DRAG_THRESHOLD
+                            });
+                            state.last_click = if double {
+                                None
+                            } else {
+                                Some((now, cursor_position))
+                            };
+
+                            if state.modifiers.alt() {
+                                PlotMessage::SelectBoundary(end, mode)
+                            } else if double {
+                                PlotMessage::SelectChain(end, mode)
+                            } else {
+                                let radius = VERTEX_PICK / state.transform.scale;
+                                PlotMessage::Select(end, radius, mode)
+                            }
+                        })
+                    }
                     _ => None,
                 };
 
@@ -164,38 +324,89 @@ fn draw_bound(bound: &MarkedBound, transform: &Transform, frame: &mut Frame) {
     let mut clicked_builder = Builder::new();
     let mut force_builder = Builder::new();
     let mut constraint_builder = Builder::new();
+    let mut combined_builder = Builder::new();
+    let mut spring_builder = Builder::new();
 
     for (edge, mark) in bound.edges_and_marks() {
-        if mark.is_clicked() {
-            build_edge(&mut clicked_builder, edge, transform);
-        } else if mark.is_force() {
-            build_edge(&mut force_builder, edge, transform);
-        } else if mark.is_constraint() {
-            build_edge(&mut constraint_builder, edge, transform);
-        } else {
-            build_edge(&mut blank_builder, edge, transform);
+        // pressures get arrows along the normal, pointing the way they push
+        for annot in mark.annots.iter() {
+            if let Annotation::Pressure(pr) = annot {
+                let n = outward_normal(edge);
+                let dir = if *pr >= 0.0 { n * -1.0 } else { n };
+                build_arrow(&mut force_builder, edge_mid(edge), dir, transform);
+            }
         }
+
+        let builder = match mark_rgb(mark) {
+            CLICKED_RGB => &mut clicked_builder,
+            COMBINED_RGB => &mut combined_builder,
+            FORCE_RGB => &mut force_builder,
+            CONSTRAINT_RGB => &mut constraint_builder,
+            SPRING_RGB => &mut spring_builder,
+            _ => &mut blank_builder,
+        };
+        build_edge(builder, edge, transform);
     }
 
     let blank_path = blank_builder.build();
     let clicked_path = clicked_builder.build();
     let force_path = force_builder.build();
     let constraint_path = constraint_builder.build();
+    let combined_path = combined_builder.build();
+    let spring_path = spring_builder.build();
 
+    let blank_stroke = Stroke::default().with_width(2.0).with_color(rgb(BLANK_RGB));
     let clicked_stroke = Stroke::default()
         .with_width(2.0)
-        .with_color(Color::from_rgb8(0, 150, 250));
-    let force_stroke = Stroke::default()
-        .with_width(2.0)
-        .with_color(Color::from_rgb8(250, 150, 0));
+        .with_color(rgb(CLICKED_RGB));
+    let force_stroke = Stroke::default().with_width(2.0).with_color(rgb(FORCE_RGB));
     let constraint_stroke = Stroke::default()
         .with_width(2.0)
-        .with_color(Color::from_rgb8(0, 250, 0));
+        .with_color(rgb(CONSTRAINT_RGB));
+    let combined_stroke = Stroke::default()
+        .with_width(2.0)
+        .with_color(rgb(COMBINED_RGB));
+    let spring_stroke = Stroke::default()
+        .with_width(2.0)
+        .with_color(rgb(SPRING_RGB));
 
-    frame.stroke(&blank_path, Stroke::default().with_width(2.0));
+    frame.stroke(&blank_path, blank_stroke);
     frame.stroke(&clicked_path, clicked_stroke);
     frame.stroke(&force_path, force_stroke);
     frame.stroke(&constraint_path, constraint_stroke);
+    frame.stroke(&combined_path, combined_stroke);
+    frame.stroke(&spring_path, spring_stroke);
+
+    // only vertices carrying something are drawn
+    for (p, mark) in bound.vertices_and_marks() {
+        let color = mark_rgb(mark);
+        if color == BLANK_RGB {
+            continue;
+        }
+
+        frame.fill(
+            &Path::circle(transform.forward(p), VERTEX_RADIUS),
+            rgb(color),
+        );
+    }
+}
+
+fn build_arrow(
+    builder: &mut Builder,
+    tip: spacemath::two::Point,
+    dir: spacemath::two::Point,
+    transform: &Transform,
+) {
+    // arrow of fixed screen size ending at tip, dir is a unit vector in model space
+    let tip = transform.forward(tip);
+    let d = iced::Vector::new(dir.x as f32, -1.0 * dir.y as f32);
+    let side = iced::Vector::new(-d.y, d.x);
+
+    builder.move_to(tip - d * ARROW_LEN);
+    builder.line_to(tip);
+    builder.move_to(tip - d * ARROW_HEAD + side * (ARROW_HEAD / 2.0));
+    builder.line_to(tip);
+    builder.line_to(tip - d * ARROW_HEAD - side * (ARROW_HEAD / 2.0));
 }
 
 fn build_edge(builder: &mut Builder, edge: &Edge, transform: &Transform) {
@@ -0,0 +1,451 @@
+use std::rc::Rc;
+
+use spacemath::two::Point;
+
+// distributed loads that vary along a chain of edges
+// a chain is parameterised by arc length s (model units) and t = s / total length
+#[derive(Debug, Clone, PartialEq)]
+pub enum Profile {
+    Linear { start: (f64, f64), end: (f64, f64) },
+    Expr { fx: Expr, fy: Expr },
+}
+
+impl Profile {
+    pub fn parse(x: &str) -> Result<Self, String> {
+        // "(1, 0) -> (2, 0)" for linear profiles, "fx ; fy" for expressions of x, y, s, t
+        if let Some((a, b)) = x.split_once("->") {
+            let start = parse_pair(a.trim()).ok_or("ill-formed linear profile start")?;
+            let end = parse_pair(b.trim()).ok_or("ill-formed linear profile end")?;
+
+            return Ok(Self::Linear { start, end });
+        }
+
+        let (fx, fy) = x
+            .split_once(';')
+            .ok_or("expected \"fx ; fy\" or \"(fx, fy) -> (fx, fy)\"")?;
+
+        Ok(Self::Expr {
+            fx: Expr::parse(fx)?,
+            fy: Expr::parse(fy)?,
+        })
+    }
+
+    pub fn acts_along(&self) -> (bool, bool) {
+        // whether the x and y components can be non-zero anywhere along the chain
+        match self {
+            Profile::Linear { start, end } => (
+                start.0 != 0.0 || end.0 != 0.0,
+                start.1 != 0.0 || end.1 != 0.0,
+            ),
+            Profile::Expr { fx, fy } => (*fx != Expr::Num(0.0), *fy != Expr::Num(0.0)),
+        }
+    }
+
+    pub fn eval(&self, env: &Env) -> (f64, f64) {
+        match self {
+            Profile::Linear { start, end } => (
+                start.0 + env.t * (end.0 - start.0),
+                start.1 + env.t * (end.1 - start.1),
+            ),
+            Profile::Expr { fx, fy } => (fx.eval(env), fy.eval(env)),
+        }
+    }
+}
+
+fn parse_pair(x: &str) -> Option<(f64, f64)> {
+    let x = x.strip_prefix('(')?.strip_suffix(')')?;
+    let (a, b) = x.split_once(',')?;
+
+    Some((a.trim().parse().ok()?, b.trim().parse().ok()?))
+}
+
+// a profile applied to one edge: the edge covers arc lengths span.0 to span.1 of a chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileLoad {
+    pub profile: Rc<Profile>,
+    pub span: (f64, f64),
+    pub total: f64,
+}
+
+impl ProfileLoad {
+    pub fn sub_span(&self, a: f64, b: f64) -> Self {
+        // the part of this load between fractions a and b of the edge
+        let (s0, s1) = self.span;
+
+        Self {
+            profile: self.profile.clone(),
+            span: (s0 + a * (s1 - s0), s0 + b * (s1 - s0)),
+            total: self.total,
+        }
+    }
+
+    pub fn traction_at(&self, mid: Point) -> (f64, f64) {
+        // evaluated once per edge, at the middle of its span
+        let s = (self.span.0 + self.span.1) / 2.0;
+        let t = if self.total > 0.0 {
+            s / self.total
+        } else {
+            0.0
+        };
+
+        self.profile.eval(&Env {
+            x: mid.x,
+            y: mid.y,
+            s,
+            t,
+        })
+    }
+}
+
+pub struct Env {
+    pub x: f64,
+    pub y: f64,
+    pub s: f64,
+    pub t: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Var {
+    X,
+    Y,
+    S,
+    T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Func {
+    Sin,
+    Cos,
+    Tan,
+    Sqrt,
+    Abs,
+    Exp,
+    Ln,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Num(f64),
+    Var(Var),
+    Neg(Box<Expr>),
+    Bin(Op, Box<Expr>, Box<Expr>),
+    Call(Func, Box<Expr>),
+}
+
+impl Expr {
+    pub fn parse(x: &str) -> Result<Self, String> {
+        let tokens = tokenize(x)?;
+        let mut parser = Parser { tokens, pos: 0 };
+
+        let expr = parser.expr()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(t) => Err(format!("unexpected {:?} in \"{}\"", t, x.trim())),
+        }
+    }
+
+    pub fn eval(&self, env: &Env) -> f64 {
+        match self {
+            Expr::Num(v) => *v,
+            Expr::Var(Var::X) => env.x,
+            Expr::Var(Var::Y) => env.y,
+            Expr::Var(Var::S) => env.s,
+            Expr::Var(Var::T) => env.t,
+            Expr::Neg(e) => -e.eval(env),
+            Expr::Bin(op, a, b) => {
+                let (a, b) = (a.eval(env), b.eval(env));
+                match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div => a / b,
+                    Op::Pow => a.powf(b),
+                }
+            }
+            Expr::Call(f, e) => {
+                let v = e.eval(env);
+                match f {
+                    Func::Sin => v.sin(),
+                    Func::Cos => v.cos(),
+                    Func::Tan => v.tan(),
+                    Func::Sqrt => v.sqrt(),
+                    Func::Abs => v.abs(),
+                    Func::Exp => v.exp(),
+                    Func::Ln => v.ln(),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Sym(char),
+}
+
+fn tokenize(x: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = x.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // exponent, e.g. 2.1e11 or 1e-3
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+
+            let text: String = chars[start..i].iter().collect();
+            let v = text
+                .parse()
+                .map_err(|_| format!("ill-formed number \"{}\"", text))?;
+            tokens.push(Token::Num(v));
+        } else if c.is_alphabetic() {
+            let start = i;
+            while i < chars.len() && chars[i].is_alphanumeric() {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if "+-*/^()".contains(c) {
+            tokens.push(Token::Sym(c));
+            i += 1;
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        }
+    }
+
+    Ok(tokens)
+}
+
+// recursive descent over
+//   expr  := term (('+' | '-') term)*
+//   term  := unary (('*' | '/') unary)*
+//   unary := '-' unary | power
+//   power := atom ('^' unary)?
+//   atom  := number | var | const | func '(' expr ')' | '(' expr ')'
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Sym(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+
+        loop {
+            let op = if self.eat('+') {
+                Op::Add
+            } else if self.eat('-') {
+                Op::Sub
+            } else {
+                return Ok(lhs);
+            };
+
+            lhs = Expr::Bin(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+
+        loop {
+            let op = if self.eat('*') {
+                Op::Mul
+            } else if self.eat('/') {
+                Op::Div
+            } else {
+                return Ok(lhs);
+            };
+
+            lhs = Expr::Bin(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.atom()?;
+
+        if self.eat('^') {
+            return Ok(Expr::Bin(Op::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Num(v)) => Ok(Expr::Num(v)),
+            Some(Token::Sym('(')) => {
+                let e = self.expr()?;
+                if !self.eat(')') {
+                    return Err("missing ')'".to_string());
+                }
+                Ok(e)
+            }
+            Some(Token::Ident(name)) => {
+                let var = match name.as_str() {
+                    "x" => Some(Expr::Var(Var::X)),
+                    "y" => Some(Expr::Var(Var::Y)),
+                    "s" => Some(Expr::Var(Var::S)),
+                    "t" => Some(Expr::Var(Var::T)),
+                    "pi" => Some(Expr::Num(std::f64::consts::PI)),
+                    _ => None,
+                };
+                if let Some(v) = var {
+                    return Ok(v);
+                }
+
+                let func = match name.as_str() {
+                    "sin" => Func::Sin,
+                    "cos" => Func::Cos,
+                    "tan" => Func::Tan,
+                    "sqrt" => Func::Sqrt,
+                    "abs" => Func::Abs,
+                    "exp" => Func::Exp,
+                    "ln" => Func::Ln,
+                    _ => return Err(format!("unknown name \"{}\"", name)),
+                };
+
+                if !self.eat('(') {
+                    return Err(format!("expected '(' after {}", name));
+                }
+                let arg = self.expr()?;
+                if !self.eat(')') {
+                    return Err("missing ')'".to_string());
+                }
+
+                Ok(Expr::Call(func, Box::new(arg)))
+            }
+            Some(t) => Err(format!("unexpected {:?}", t)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(x: f64, y: f64, s: f64, t: f64) -> Env {
+        Env { x, y, s, t }
+    }
+
+    #[test]
+    fn linear() {
+        let p = Profile::parse("(1, 0) -> (2, -4)").unwrap();
+
+        assert_eq!(
+            p,
+            Profile::Linear {
+                start: (1.0, 0.0),
+                end: (2.0, -4.0)
+            }
+        );
+        assert_eq!(p.eval(&env(0.0, 0.0, 0.0, 0.5)), (1.5, -2.0));
+    }
+
+    #[test]
+    fn expressions() {
+        let p = Profile::parse("2*x + 1 ; -s^2").unwrap();
+        assert_eq!(p.eval(&env(3.0, 0.0, 2.0, 0.0)), (7.0, -4.0));
+
+        // powers bind right to left and tighter than a leading minus
+        let p = Profile::parse("2^3^2 ; -2^2").unwrap();
+        assert_eq!(p.eval(&env(0.0, 0.0, 0.0, 0.0)), (512.0, -4.0));
+
+        let p = Profile::parse("sin(pi / 2) * 1e3 ; sqrt(t) - (y - 1)").unwrap();
+        assert_eq!(p.eval(&env(0.0, 3.0, 0.0, 0.25)), (1e3, -1.5));
+    }
+
+    #[test]
+    fn errors() {
+        let err = |x: &str| Profile::parse(x).unwrap_err();
+
+        assert_eq!(err("(1, 0) -> 2"), "ill-formed linear profile end");
+        assert_eq!(err("1, 0 -> (2, 0)"), "ill-formed linear profile start");
+        assert_eq!(err("x"), "expected \"fx ; fy\" or \"(fx, fy) -> (fx, fy)\"");
+        assert_eq!(err("foo(x) ; 0"), "unknown name \"foo\"");
+        assert_eq!(err("sin x ; 0"), "expected '(' after sin");
+        assert_eq!(err("(x ; 0"), "missing ')'");
+        assert_eq!(err("1 $ 2 ; 0"), "unexpected character '$'");
+        assert_eq!(err("x y ; 0"), "unexpected Ident(\"y\") in \"x y\"");
+        assert_eq!(err("x ; 2 *"), "unexpected end of expression");
+    }
+
+    #[test]
+    fn directions() {
+        let along = |x: &str| Profile::parse(x).unwrap().acts_along();
+
+        assert_eq!(along("(0, 1) -> (0, 2)"), (false, true));
+        assert_eq!(along("(1, 0) -> (0, 0)"), (true, false));
+        assert_eq!(along("0 ; x"), (false, true));
+        assert_eq!(along("s ; 0"), (true, false));
+    }
+
+    #[test]
+    fn load_spans() {
+        let load = ProfileLoad {
+            profile: Rc::new(Profile::parse("(0, 0) -> (10, 0)").unwrap()),
+            span: (2.0, 4.0),
+            total: 10.0,
+        };
+
+        // evaluated at the middle of the span, t = 3 / 10
+        assert_eq!(load.traction_at(Point::new(0.0, 0.0)), (3.0, 0.0));
+        assert_eq!(load.sub_span(0.5, 1.0).span, (3.0, 4.0));
+    }
+}
@@ -0,0 +1,160 @@
+use std::path::Path;
+
+use ::dxf::entities::{Entity, EntityType};
+use ::dxf::enums::{AcadVersion, Units};
+use ::dxf::tables::Layer;
+use ::dxf::{Color, Drawing};
+
+use spacemath::two::boundary::Edge;
+use spacemath::two::Point;
+
+use crate::app::mark::{Annotation, Mark, MarkedModel};
+use crate::app::plot::{annotation_rgb, COMBINED_RGB, CONSTRAINT_RGB, FORCE_RGB, SPRING_RGB};
+
+const GEOMETRY_LAYER: &str = "GEOMETRY";
+
+// writes the processed geometry back out for cad: one entity per edge, with
+// annotated edges placed on a layer named after their annotation
+pub fn write_dxf<T: AsRef<Path>>(model: &MarkedModel, path: T, units: Units) {
+    let mut drawing = Drawing::new();
+    // $INSUNITS and layer names with '.' or '+' need at least R2000, the default is R12
+    drawing.header.version = AcadVersion::R2000;
+    drawing.header.default_drawing_units = units; // $INSUNITS
+
+    let mut layers: Vec<String> = Vec::new();
+    add_layer(
+        &mut drawing,
+        &mut layers,
+        GEOMETRY_LAYER,
+        Color::from_index(7),
+    );
+
+    for b in model.bounds() {
+        for (edge, mark) in b.edges_and_marks() {
+            // an entity sits on one layer, so combined annotations share a joint layer
+            let layer = if mark.is_annotated() {
+                let names: Vec<String> = mark.annots.iter().map(layer_name).collect();
+                let name = names.join("+");
+
+                add_layer(&mut drawing, &mut layers, &name, layer_color(mark));
+                name
+            } else {
+                GEOMETRY_LAYER.to_string()
+            };
+
+            let mut ent = Entity::new(entity_type(edge));
+            ent.common.layer = layer;
+            drawing.add_entity(ent);
+        }
+    }
+
+    // annotated vertices become points on their annotation's layer
+    for b in model.bounds() {
+        for (p, mark) in b.vertices_and_marks().filter(|(_, m)| m.is_annotated()) {
+            let names: Vec<String> = mark.annots.iter().map(layer_name).collect();
+            let name = format!("POINT_{}", names.join("+"));
+
+            add_layer(&mut drawing, &mut layers, &name, layer_color(mark));
+
+            let mut ent = Entity::new(EntityType::ModelPoint(::dxf::entities::ModelPoint::new(
+                dxf_point(p),
+            )));
+            ent.common.layer = name;
+            drawing.add_entity(ent);
+        }
+    }
+
+    drawing.save_file(path).unwrap();
+}
+
+fn layer_color(mark: &Mark) -> Color {
+    // the closest indexed colour to the one the app draws, ignoring the selection
+    match annotation_rgb(mark) {
+        COMBINED_RGB => Color::from_index(200),
+        FORCE_RGB => Color::from_index(30),
+        SPRING_RGB => Color::from_index(32),
+        CONSTRAINT_RGB => Color::from_index(3),
+        _ => Color::from_index(7),
+    }
+}
+
+fn add_layer(drawing: &mut Drawing, layers: &mut Vec<String>, name: &str, color: Color) {
+    if layers.iter().any(|l| l == name) {
+        return;
+    }
+
+    drawing.add_layer(Layer {
+        name: name.to_string(),
+        color,
+        ..Default::default()
+    });
+    layers.push(name.to_string());
+}
+
+fn layer_name(annot: &Annotation) -> String {
+    match annot {
+        Annotation::ConstrainX => "CONSTRAIN_X".to_string(),
+        Annotation::ConstrainY => "CONSTRAIN_Y".to_string(),
+        Annotation::ConstrainXY => "CONSTRAIN_XY".to_string(),
+        Annotation::ConstrainTangent => "CONSTRAIN_TANGENT".to_string(),
+        Annotation::Displacement(dx, dy) => {
+            let component = |d: &Option<f64>| match d {
+                Some(d) => layer_value(*d),
+                None => "FREE".to_string(),
+            };
+            format!("DISPLACE_{}_{}", component(dx), component(dy))
+        }
+        Annotation::Force(x, y) => format!("FORCE_{}_{}", layer_value(*x), layer_value(*y)),
+        Annotation::Pressure(p) => format!("PRESSURE_{}", layer_value(*p)),
+        Annotation::Profile(_) => "PROFILE".to_string(),
+        Annotation::Spring(kn, kt) => {
+            format!("SPRING_{}_{}", layer_value(*kn), layer_value(*kt))
+        }
+        Annotation::Empty => GEOMETRY_LAYER.to_string(),
+    }
+}
+
+fn layer_value(x: f64) -> String {
+    // rounded so float noise like 0.30000000000000004 doesn't end up in (or split) layers
+    let r = (x * 1e6).round() / 1e6;
+    if r == 0.0 {
+        "0".to_string()
+    } else {
+        r.to_string()
+    }
+}
+
+fn dxf_point(p: Point) -> ::dxf::Point {
+    ::dxf::Point::new(p.x, p.y, 0.0)
+}
+
+fn entity_type(edge: &Edge) -> EntityType {
+    match *edge {
+        Edge::Arc(a) => {
+            let (p_ang, q_ang) = a.pq_ang_unbounded();
+            let center = dxf_point(a.center());
+
+            if (q_ang - p_ang).abs() > std::f64::consts::TAU - 1e-9 {
+                return EntityType::Circle(::dxf::entities::Circle::new(center, a.radius()));
+            }
+
+            // dxf arcs always run ccw from start to end
+            let (start, end) = if q_ang > p_ang {
+                (p_ang, q_ang)
+            } else {
+                (q_ang, p_ang)
+            };
+
+            EntityType::Arc(::dxf::entities::Arc::new(
+                center,
+                a.radius(),
+                start.to_degrees(),
+                end.to_degrees(),
+            ))
+        }
+        Edge::Segment(_) => EntityType::Line(::dxf::entities::Line::new(
+            dxf_point(edge.p()),
+            dxf_point(edge.q()),
+        )),
+    }
+}
@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+
+use spacemath::two::boundary::Edge;
+use spacemath::two::dist::Dist;
+use spacemath::two::Point;
+
+use crate::app::body::BodyLoads;
+use crate::app::mark::{Annotation, MarkedBound};
+use crate::geom::{edge_mid, node_normal, pressure_traction};
+use crate::material::Material;
+use crate::writer::Condition;
+
+// writes an abaqus / calculix .inp skeleton (plane stress, plane strain or axisymmetric)
+// for cross-checking bugi results
+// the boundary nodes, node sets, section and load cards are filled in; the mesh is not
+pub struct InpWriter {
+    nodes: Vec<Point>,
+    node_ids: HashMap<String, usize>,
+
+    // one node set per distinct annotation, in order of first appearance
+    // edge and vertex annotations are kept apart (flagged true for vertices)
+    sets: Vec<(Annotation, bool, Vec<usize>)>,
+
+    // named groups, written as node sets prefixed GROUP_ so they can't clash with the sets above
+    groups: Vec<(String, Vec<usize>)>,
+
+    // tangent constraints need a local frame per node, so a node shared by two
+    // constrained edges gets one frame from the sum of both edges' normals there
+    tangents: HashMap<usize, Point>,
+
+    // nodal loads accumulated from distributed forces
+    loads: HashMap<usize, (f64, f64)>,
+
+    body_loads: BodyLoads,
+
+    // for unit conversions (inp is written in meters, consistent with bbnd)
+    scale: f64,
+}
+
+impl InpWriter {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            node_ids: HashMap::new(),
+            sets: Vec::new(),
+            groups: Vec::new(),
+            tangents: HashMap::new(),
+            loads: HashMap::new(),
+            body_loads: BodyLoads::default(),
+
+            scale: 1.0,
+        }
+    }
+
+    pub fn scale(self, scale: f64) -> Self {
+        Self { scale, ..self }
+    }
+
+    pub fn body_loads(self, body_loads: BodyLoads) -> Self {
+        Self { body_loads, ..self }
+    }
+
+    fn node_id(&mut self, p: Point) -> usize {
+        let p = p * self.scale;
+        let key = format!("{:.5} {:.5}", p.x, p.y);
+
+        if let Some(&id) = self.node_ids.get(&key) {
+            return id;
+        }
+
+        // calculix node numbers start at 1
+        self.nodes.push(p);
+        let id = self.nodes.len();
+        self.node_ids.insert(key, id);
+
+        id
+    }
+
+    fn add_to_set(&mut self, annot: &Annotation, vertex: bool, ids: &[usize]) {
+        let pos = self
+            .sets
+            .iter()
+            .position(|(a, v, _)| same_set(a, annot) && *v == vertex);
+
+        let set = match pos {
+            Some(i) => &mut self.sets[i].2,
+            None => {
+                self.sets.push((annot.clone(), vertex, Vec::new()));
+                &mut self.sets.last_mut().unwrap().2
+            }
+        };
+
+        for &id in ids {
+            if !set.contains(&id) {
+                set.push(id);
+            }
+        }
+    }
+
+    fn add_to_groups(&mut self, groups: &[String], ids: &[usize]) {
+        for name in groups {
+            let pos = self.groups.iter().position(|(g, _)| g == name);
+
+            let group = match pos {
+                Some(i) => &mut self.groups[i].1,
+                None => {
+                    self.groups.push((name.clone(), Vec::new()));
+                    &mut self.groups.last_mut().unwrap().1
+                }
+            };
+
+            for &id in ids {
+                if !group.contains(&id) {
+                    group.push(id);
+                }
+            }
+        }
+    }
+
+    pub fn add_boundary(&mut self, marked_bound: MarkedBound) {
+        // treats all edges as segments. Break arcs down before this point
+        let parents = marked_bound.parents();
+        for ((edge, mark), parent) in marked_bound.edges_and_marks().zip(parents) {
+            // register every vertex so the whole outline is available for meshing
+            let ids = [self.node_id(edge.p()), self.node_id(edge.q())];
+            self.add_to_groups(&mark.groups, &ids);
+
+            for annot in mark.annots.iter() {
+                self.add_to_set(annot, false, &ids);
+
+                let traction = match annot {
+                    Annotation::Force(fx, fy) => Some((*fx, *fy)),
+                    Annotation::Pressure(pr) => Some(pressure_traction(*pr, edge)),
+                    Annotation::Profile(pl) => Some(pl.traction_at(edge_mid(edge))),
+                    _ => None,
+                };
+
+                if let Some((fx, fy)) = traction {
+                    // lump the traction onto the edge's end nodes
+                    // the chord is only the edge's length once arcs are segmentified
+                    if let Edge::Arc(_) = edge {
+                        eprintln!("WARNING: load on an unsegmented arc left out of the inp");
+                        continue;
+                    }
+                    let len = edge.p().dist(edge.q()) * self.scale;
+                    for id in ids {
+                        let load = self.loads.entry(id).or_insert((0.0, 0.0));
+                        load.0 += fx * len / 2.0;
+                        load.1 += fy * len / 2.0;
+                    }
+                }
+
+                if *annot == Annotation::ConstrainTangent {
+                    for (x, id) in [edge.p(), edge.q()].into_iter().zip(ids) {
+                        let n = node_normal(x, edge.p(), edge.q(), parent);
+                        let sum = self.tangents.entry(id).or_insert(Point::origin());
+                        *sum = *sum + n;
+                    }
+                }
+            }
+        }
+
+        for (p, mark) in marked_bound.vertices_and_marks() {
+            let id = self.node_id(p);
+            self.add_to_groups(&mark.groups, &[id]);
+
+            for annot in mark.annots.iter() {
+                self.add_to_set(annot, true, &[id]);
+
+                if let &Annotation::Force(fx, fy) = annot {
+                    let load = self.loads.entry(id).or_insert((0.0, 0.0));
+                    load.0 += fx;
+                    load.1 += fy;
+                }
+            }
+        }
+    }
+
+    pub fn write<T: AsRef<Path>>(self, path: T, condition: Condition, regions: &[(Material, f64)]) {
+        let mut to_write = String::new();
+
+        let element = match condition {
+            Condition::PlaneStress => "CPS6",
+            Condition::PlaneStrain => "CPE6",
+            Condition::Axisymmetric => "CAX6",
+        };
+
+        to_write.push_str("*HEADING\n");
+        to_write.push_str(&format!(
+            "bugi_laco export, {} skeleton (units: m)\n",
+            condition
+        ));
+
+        // boundary nodes
+        to_write.push_str("*NODE, NSET=NBOUND\n");
+        for (i, p) in self.nodes.iter().enumerate() {
+            to_write.push_str(&format!("{}, {:.8}, {:.8}\n", i + 1, p.x, p.y));
+        }
+
+        to_write
+            .push_str("** mesh the region enclosed by NBOUND and add the elements here, e.g.\n");
+        // one element set per region when there are several
+        let elsets: Vec<String> = if regions.len() > 1 {
+            (1..=regions.len())
+                .map(|i| format!("EREGION_{}", i))
+                .collect()
+        } else {
+            vec!["EALL".to_string()]
+        };
+        for elset in elsets.iter() {
+            to_write.push_str(&format!("** *ELEMENT, TYPE={}, ELSET={}\n", element, elset));
+        }
+
+        // named node sets, one per annotation
+        let names: Vec<String> = set_names(&self.sets);
+        for ((_, _, ids), name) in self.sets.iter().zip(names.iter()) {
+            to_write.push_str(&format!("*NSET, NSET={}\n", name));
+            to_write.push_str(&id_lines(ids));
+        }
+
+        for (name, ids) in self.groups.iter() {
+            to_write.push_str(&format!("*NSET, NSET=GROUP_{}\n", name));
+            to_write.push_str(&id_lines(ids));
+        }
+
+        let mut tangents: Vec<(usize, Point)> = self.tangents.into_iter().collect();
+        tangents.sort_by_key(|&(id, _)| id);
+
+        for (id, _) in tangents.iter() {
+            to_write.push_str(&format!("*NSET, NSET=TANGENT_{}\n", id));
+            to_write.push_str(&id_lines(&[*id]));
+        }
+
+        // local frames for tangent constraints: local x is the node's averaged normal
+        for (id, n) in tangents.iter() {
+            let (s, c) = n.ang().sin_cos();
+            to_write.push_str(&format!("*TRANSFORM, NSET=TANGENT_{}, TYPE=R\n", id));
+            to_write.push_str(&format!(
+                "{:.8}, {:.8}, 0.0, {:.8}, {:.8}, 0.0\n",
+                c, s, -s, c
+            ));
+        }
+
+        // no element-free spring card exists, so leave the foundation to the mesher
+        for ((annot, _, _), name) in self.sets.iter().zip(names.iter()) {
+            if let Annotation::Spring(kn, kt) = annot {
+                to_write.push_str(&format!(
+                    "** {}: elastic foundation kn = {}, kt = {} per unit length, add SPRINGA elements\n",
+                    name, kn, kt
+                ));
+            }
+        }
+
+        // materials, each written once, and a section per region
+        let mut written: Vec<&str> = Vec::new();
+        for (material, _) in regions.iter() {
+            if written.contains(&material.name.as_str()) {
+                continue;
+            }
+            written.push(&material.name);
+
+            to_write.push_str(&format!("*MATERIAL, NAME={}\n", material.name));
+            to_write.push_str(&format!(
+                "*ELASTIC\n{}, {}\n*DENSITY\n{}\n",
+                material.youngs_modulus, material.poisson_ratio, material.density
+            ));
+            to_write.push_str(&format!("** yield stress {} Pa\n", material.yield_stress));
+        }
+
+        for ((material, thickness), elset) in regions.iter().zip(elsets.iter()) {
+            to_write.push_str(&format!(
+                "*SOLID SECTION, ELSET={}, MATERIAL={}\n",
+                elset, material.name
+            ));
+            if condition.needs_thickness() {
+                to_write.push_str(&format!("{}\n", thickness));
+            }
+        }
+
+        to_write.push_str("*STEP\n*STATIC\n");
+
+        // boundary cards
+        to_write.push_str("*BOUNDARY\n");
+        for ((annot, _, _), name) in self.sets.iter().zip(names.iter()) {
+            match annot {
+                Annotation::ConstrainX => to_write.push_str(&format!("{}, 1, 1\n", name)),
+                Annotation::ConstrainY => to_write.push_str(&format!("{}, 2, 2\n", name)),
+                Annotation::ConstrainXY => to_write.push_str(&format!("{}, 1, 2\n", name)),
+                Annotation::Displacement(dx, dy) => {
+                    if let Some(dx) = dx {
+                        to_write.push_str(&format!("{}, 1, 1, {:.8}\n", name, dx * self.scale));
+                    }
+                    if let Some(dy) = dy {
+                        to_write.push_str(&format!("{}, 2, 2, {:.8}\n", name, dy * self.scale));
+                    }
+                }
+                _ => (),
+            }
+        }
+        for (id, _) in tangents.iter() {
+            to_write.push_str(&format!("TANGENT_{}, 1, 1\n", id));
+        }
+
+        // load cards, nodal values lumped from the distributed forces plus point forces
+        if !self.loads.is_empty() {
+            let mut loads: Vec<(usize, (f64, f64))> = self.loads.into_iter().collect();
+            loads.sort_by_key(|&(id, _)| id);
+
+            to_write.push_str("*CLOAD\n");
+            for (id, (fx, fy)) in loads {
+                to_write.push_str(&format!("{}, 1, {:.8}\n", id, fx));
+                to_write.push_str(&format!("{}, 2, {:.8}\n", id, fy));
+            }
+        }
+
+        // body loads on every element set, rotation is about the out-of-plane axis
+        if !self.body_loads.is_empty() {
+            to_write.push_str("*DLOAD\n");
+
+            for elset in elsets.iter() {
+                if let Some((gx, gy)) = self.body_loads.gravity {
+                    let g = gx.hypot(gy);
+                    if g > 0.0 {
+                        to_write.push_str(&format!(
+                            "{}, GRAV, {:.8}, {:.8}, {:.8}, 0.0\n",
+                            elset,
+                            g,
+                            gx / g,
+                            gy / g
+                        ));
+                    }
+                }
+
+                if let Some((omega, c)) = self.body_loads.rotation {
+                    let c = c * self.scale;
+                    to_write.push_str(&format!(
+                        "{}, CENTRIF, {:.8}, {:.8}, {:.8}, 0.0, 0.0, 0.0, 1.0\n",
+                        elset,
+                        omega * omega,
+                        c.x,
+                        c.y
+                    ));
+                }
+            }
+        }
+
+        to_write.push_str("*NODE FILE\nU\n*EL FILE\nS\n*END STEP");
+
+        std::fs::write(path, to_write).unwrap();
+    }
+}
+
+fn set_names(sets: &[(Annotation, bool, Vec<usize>)]) -> Vec<String> {
+    let mut force_count = 0;
+    let mut displacement_count = 0;
+    let mut spring_count = 0;
+
+    sets.iter()
+        .map(|(annot, vertex, _)| {
+            let name = match annot {
+                Annotation::ConstrainX => "CONSTRAIN_X".to_string(),
+                Annotation::ConstrainY => "CONSTRAIN_Y".to_string(),
+                Annotation::ConstrainXY => "CONSTRAIN_XY".to_string(),
+                Annotation::ConstrainTangent => "CONSTRAIN_TANGENT".to_string(),
+                Annotation::Displacement(_, _) => {
+                    displacement_count += 1;
+                    format!("DISPLACE_{}", displacement_count)
+                }
+                Annotation::Spring(_, _) => {
+                    spring_count += 1;
+                    format!("SPRING_{}", spring_count)
+                }
+                Annotation::Force(_, _) => {
+                    force_count += 1;
+                    format!("FORCE_{}", force_count)
+                }
+                Annotation::Pressure(_) => {
+                    force_count += 1;
+                    format!("PRESSURE_{}", force_count)
+                }
+                Annotation::Profile(_) => {
+                    force_count += 1;
+                    format!("PROFILE_{}", force_count)
+                }
+                Annotation::Empty => unreachable!(),
+            };
+
+            if *vertex {
+                format!("POINT_{}", name)
+            } else {
+                name
+            }
+        })
+        .collect()
+}
+
+fn same_set(a: &Annotation, b: &Annotation) -> bool {
+    // every edge under a profile carries its own span, the set is the whole profile
+    match (a, b) {
+        (Annotation::Profile(x), Annotation::Profile(y)) => Rc::ptr_eq(&x.profile, &y.profile),
+        _ => a == b,
+    }
+}
+
+fn id_lines(ids: &[usize]) -> String {
+    // inp data lines hold at most 16 entries
+    let mut res = String::new();
+
+    for chunk in ids.chunks(16) {
+        let line: Vec<String> = chunk.iter().map(|id| id.to_string()).collect();
+        res.push_str(&line.join(", "));
+        res.push('\n');
+    }
+
+    res
+}
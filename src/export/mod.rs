@@ -0,0 +1,3 @@
+pub mod dxf;
+pub mod inp;
+pub mod svg;
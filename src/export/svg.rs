@@ -0,0 +1,306 @@
+use std::f64::consts::PI;
+use std::path::Path;
+
+use spacemath::two::boundary::Edge;
+use spacemath::two::Point;
+
+use crate::app::mark::{Annotation, MarkedModel};
+use crate::app::plot::{
+    annotation_rgb, BLANK_RGB, COMBINED_RGB, CONSTRAINT_RGB, FORCE_RGB, SPRING_RGB,
+};
+use crate::geom::{edge_mid, pressure_traction};
+
+// width of the drawing area in px, height follows the model's aspect ratio
+const WIDTH: f64 = 800.0;
+const MARGIN: f64 = 60.0;
+const LEGEND_HEIGHT: f64 = 150.0;
+
+// arrows and glyphs are sized in px so they read the same at any model scale
+const ARROW_LEN: f64 = 40.0;
+const GLYPH_SIZE: f64 = 8.0;
+
+// maps model coordinates (y up) onto svg coordinates (y down)
+struct View {
+    min: Point,
+    max_y: f64,
+    scale: f64,
+}
+
+impl View {
+    fn new((min, max): (Point, Point)) -> Self {
+        let span = (max.x - min.x).max(max.y - min.y);
+
+        Self {
+            min,
+            max_y: max.y,
+            scale: (WIDTH - 2.0 * MARGIN) / span,
+        }
+    }
+
+    fn forward(&self, p: Point) -> (f64, f64) {
+        (
+            MARGIN + (p.x - self.min.x) * self.scale,
+            MARGIN + (self.max_y - p.y) * self.scale,
+        )
+    }
+
+    fn height(&self) -> f64 {
+        2.0 * MARGIN + (self.max_y - self.min.y) * self.scale
+    }
+}
+
+pub fn write_svg<T: AsRef<Path>>(model: &MarkedModel, path: T) {
+    let view = View::new(model.bounding_box());
+    let height = view.height() + LEGEND_HEIGHT;
+
+    let mut to_write = String::new();
+
+    to_write.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\">\n",
+        w = WIDTH,
+        h = height
+    ));
+    to_write.push_str(&format!(
+        "<rect width=\"{:.0}\" height=\"{:.0}\" fill=\"white\"/>\n",
+        WIDTH, height
+    ));
+
+    // edges first so the arrows and glyphs sit on top
+    for b in model.bounds() {
+        for (edge, mark) in b.edges_and_marks() {
+            to_write.push_str(&format!(
+                "<path d=\"{}\" stroke=\"{}\" stroke-width=\"2\" fill=\"none\"/>\n",
+                edge_path(edge, &view),
+                hex(annotation_rgb(mark))
+            ));
+        }
+    }
+
+    for b in model.bounds() {
+        for (edge, mark) in b.edges_and_marks() {
+            let mid = view.forward(edge_mid(edge));
+
+            for annot in mark.annots.iter() {
+                match annot {
+                    &Annotation::Force(x, y) => to_write.push_str(&force_arrow(mid, (x, y))),
+                    Annotation::Pressure(pr) => {
+                        to_write.push_str(&force_arrow(mid, pressure_traction(*pr, edge)))
+                    }
+                    Annotation::Profile(pl) => {
+                        to_write.push_str(&force_arrow(mid, pl.traction_at(edge_mid(edge))))
+                    }
+                    annot => to_write.push_str(&constraint_glyph(mid, annot)),
+                }
+            }
+        }
+    }
+
+    // annotated vertices get a dot plus the same arrow or glyph as an edge
+    for b in model.bounds() {
+        for (p, mark) in b.vertices_and_marks().filter(|(_, m)| m.is_annotated()) {
+            let pos = view.forward(p);
+
+            to_write.push_str(&format!(
+                "<circle cx=\"{:.3}\" cy=\"{:.3}\" r=\"{}\" fill=\"{}\"/>\n",
+                pos.0,
+                pos.1,
+                GLYPH_SIZE / 2.0,
+                hex(annotation_rgb(mark))
+            ));
+
+            for annot in mark.annots.iter() {
+                match annot {
+                    &Annotation::Force(x, y) => to_write.push_str(&force_arrow(pos, (x, y))),
+                    annot => to_write.push_str(&constraint_glyph(pos, annot)),
+                }
+            }
+        }
+    }
+
+    to_write.push_str(&legend(view.height()));
+    to_write.push_str("</svg>\n");
+
+    std::fs::write(path, to_write).unwrap();
+}
+
+fn hex(c: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+}
+
+fn edge_path(edge: &Edge, view: &View) -> String {
+    let (px, py) = view.forward(edge.p());
+
+    match *edge {
+        Edge::Arc(a) => {
+            let (p_ang, q_ang) = a.pq_ang_unbounded();
+            let sweep = q_ang - p_ang;
+            let r = a.radius() * view.scale;
+
+            // svg is y-down, so a ccw model arc is drawn with the positive sweep flag
+            let sweep_flag = if sweep > 0.0 { 1 } else { 0 };
+
+            if sweep.abs() > 2.0 * PI - 1e-9 {
+                // full circles can't be expressed by a single arc command
+                let half = a.center() + Point::unit(p_ang + sweep / 2.0) * a.radius();
+                let (hx, hy) = view.forward(half);
+                format!(
+                    "M {:.3} {:.3} A {r:.3} {r:.3} 0 0 {f} {:.3} {:.3} A {r:.3} {r:.3} 0 0 {f} {:.3} {:.3}",
+                    px,
+                    py,
+                    hx,
+                    hy,
+                    px,
+                    py,
+                    r = r,
+                    f = sweep_flag
+                )
+            } else {
+                let (qx, qy) = view.forward(edge.q());
+                let large_flag = if sweep.abs() > PI { 1 } else { 0 };
+                format!(
+                    "M {:.3} {:.3} A {r:.3} {r:.3} 0 {} {} {:.3} {:.3}",
+                    px,
+                    py,
+                    large_flag,
+                    sweep_flag,
+                    qx,
+                    qy,
+                    r = r
+                )
+            }
+        }
+        Edge::Segment(_) => {
+            let (qx, qy) = view.forward(edge.q());
+            format!("M {:.3} {:.3} L {:.3} {:.3}", px, py, qx, qy)
+        }
+    }
+}
+
+fn force_arrow((x0, y0): (f64, f64), (fx, fy): (f64, f64)) -> String {
+    // labelled with the magnitude, the arrow shows the direction
+    let mag = (fx * fx + fy * fy).sqrt();
+    if mag == 0.0 {
+        return String::new();
+    }
+
+    // unit direction in svg coordinates
+    let (dx, dy) = (fx / mag, -fy / mag);
+
+    // the arrow points at the edge, its tail sits off the edge
+    let (tx, ty) = (x0 - dx * ARROW_LEN, y0 - dy * ARROW_LEN);
+
+    let head = GLYPH_SIZE;
+    let (lx, ly) = (
+        x0 - dx * head - dy * head / 2.0,
+        y0 - dy * head + dx * head / 2.0,
+    );
+    let (rx, ry) = (
+        x0 - dx * head + dy * head / 2.0,
+        y0 - dy * head - dx * head / 2.0,
+    );
+
+    let color = hex(FORCE_RGB);
+
+    format!(
+        "<line x1=\"{:.3}\" y1=\"{:.3}\" x2=\"{:.3}\" y2=\"{:.3}\" stroke=\"{c}\" stroke-width=\"1.5\"/>\n\
+         <polygon points=\"{:.3},{:.3} {:.3},{:.3} {:.3},{:.3}\" fill=\"{c}\"/>\n\
+         <text x=\"{:.3}\" y=\"{:.3}\" font-size=\"10\" font-family=\"sans-serif\" fill=\"{c}\">{}</text>\n",
+        tx,
+        ty,
+        x0,
+        y0,
+        x0,
+        y0,
+        lx,
+        ly,
+        rx,
+        ry,
+        tx + 2.0,
+        ty - 2.0,
+        format_magnitude(mag),
+        c = color
+    )
+}
+
+fn format_magnitude(mag: f64) -> String {
+    // three decimals, in exponent form when that would hide the value (2.000e5, 1.000e-4)
+    if (1e-2..1e4).contains(&mag) {
+        format!("{:.3}", mag)
+    } else {
+        format!("{:.3e}", mag)
+    }
+}
+
+fn constraint_glyph((x0, y0): (f64, f64), annot: &Annotation) -> String {
+    // a small support triangle under a label naming the constrained direction
+    let label = match annot {
+        Annotation::ConstrainX => "X",
+        Annotation::ConstrainY => "Y",
+        Annotation::ConstrainXY => "XY",
+        Annotation::ConstrainTangent => "T",
+        Annotation::Displacement(_, _) => "D",
+        Annotation::Spring(_, _) => "K",
+        _ => unreachable!(),
+    };
+
+    let s = GLYPH_SIZE;
+    let color = hex(if annot.is_spring() {
+        SPRING_RGB
+    } else {
+        CONSTRAINT_RGB
+    });
+
+    format!(
+        "<polygon points=\"{:.3},{:.3} {:.3},{:.3} {:.3},{:.3}\" fill=\"none\" stroke=\"{c}\" stroke-width=\"1.5\"/>\n\
+         <text x=\"{:.3}\" y=\"{:.3}\" font-size=\"10\" font-family=\"sans-serif\" fill=\"{c}\">{}</text>\n",
+        x0,
+        y0,
+        x0 - s,
+        y0 + s * 1.5,
+        x0 + s,
+        y0 + s * 1.5,
+        x0 + s * 1.5,
+        y0 + s * 1.5,
+        label,
+        c = color
+    )
+}
+
+fn legend(top: f64) -> String {
+    let entries = [
+        (BLANK_RGB, "free edge"),
+        (FORCE_RGB, "distributed force / pressure"),
+        (
+            CONSTRAINT_RGB,
+            "constraint (X, Y, XY, T = tangent, D = displacement)",
+        ),
+        (SPRING_RGB, "elastic support (K)"),
+        (COMBINED_RGB, "support and force"),
+        (FORCE_RGB, "point force (dot)"),
+        (CONSTRAINT_RGB, "point support (dot)"),
+    ];
+
+    let mut res = String::from("<g font-size=\"12\" font-family=\"sans-serif\">\n");
+
+    for (i, (c, name)) in entries.iter().enumerate() {
+        let y = top + 10.0 + 18.0 * i as f64;
+
+        res.push_str(&format!(
+            "<line x1=\"{:.0}\" y1=\"{:.1}\" x2=\"{:.0}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+            MARGIN,
+            y,
+            MARGIN + 30.0,
+            y,
+            hex(*c)
+        ));
+        res.push_str(&format!(
+            "<text x=\"{:.0}\" y=\"{:.1}\">{}</text>\n",
+            MARGIN + 40.0,
+            y + 4.0,
+            name
+        ));
+    }
+
+    res.push_str("</g>\n");
+    res
+}
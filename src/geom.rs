@@ -0,0 +1,125 @@
+use std::f64::consts::FRAC_PI_2;
+
+use spacemath::two::boundary::Edge;
+use spacemath::two::dist::Dist;
+use spacemath::two::Point;
+
+// small geometric helpers on edges that spacemath doesn't provide
+
+pub fn edge_mid(edge: &Edge) -> Point {
+    // point halfway along the edge (on the curve for arcs)
+    match *edge {
+        Edge::Arc(a) => {
+            let (p_ang, q_ang) = a.pq_ang_unbounded();
+            a.center() + Point::unit((p_ang + q_ang) / 2.0) * a.radius()
+        }
+        Edge::Segment(_) => edge.p().mid(edge.q()),
+    }
+}
+
+pub fn edge_length(edge: &Edge) -> f64 {
+    // true length, along the curve for arcs
+    match *edge {
+        Edge::Arc(a) => {
+            let (p_ang, q_ang) = a.pq_ang_unbounded();
+            a.radius() * (q_ang - p_ang).abs()
+        }
+        Edge::Segment(_) => edge.p().dist(edge.q()),
+    }
+}
+
+pub fn edge_samples(edge: &Edge, n: usize) -> Vec<Point> {
+    // n + 1 points evenly spaced along the edge, endpoints included
+    (0..=n)
+        .map(|i| {
+            let t = i as f64 / n as f64;
+
+            match *edge {
+                Edge::Arc(a) => {
+                    let (p_ang, q_ang) = a.pq_ang_unbounded();
+                    a.center() + Point::unit(p_ang + t * (q_ang - p_ang)) * a.radius()
+                }
+                Edge::Segment(_) => edge.p() + (edge.q() - edge.p()) * t,
+            }
+        })
+        .collect()
+}
+
+pub fn edge_tangents(edge: &Edge) -> (Point, Point) {
+    // unit tangents in the direction of travel at the start and end of the edge
+    match *edge {
+        Edge::Arc(a) => {
+            let (p_ang, q_ang) = a.pq_ang_unbounded();
+            let turn = if q_ang > p_ang { FRAC_PI_2 } else { -FRAC_PI_2 };
+
+            (Point::unit(p_ang + turn), Point::unit(q_ang + turn))
+        }
+        Edge::Segment(_) => {
+            let d = edge.q() - edge.p();
+            let d = d / d.dist(Point::origin());
+
+            (d, d)
+        }
+    }
+}
+
+pub fn angle_between(u: Point, v: Point) -> f64 {
+    // angle between two unit vectors, in radians
+    (u.x * v.x + u.y * v.y).clamp(-1.0, 1.0).acos()
+}
+
+pub fn outward_normal(edge: &Edge) -> Point {
+    // unit normal at the middle of the edge pointing away from the material
+    // boundaries keep the material on their left (outer ccw, inner cw), so this is the right
+    let t = match *edge {
+        Edge::Arc(a) => {
+            let (p_ang, q_ang) = a.pq_ang_unbounded();
+            let turn = if q_ang > p_ang { FRAC_PI_2 } else { -FRAC_PI_2 };
+
+            Point::unit((p_ang + q_ang) / 2.0 + turn)
+        }
+        Edge::Segment(_) => edge_tangents(edge).0,
+    };
+
+    Point::new(t.y, -t.x)
+}
+
+pub fn pressure_traction(pr: f64, edge: &Edge) -> (f64, f64) {
+    // a positive pressure pushes into the material, against the outward normal
+    let n = outward_normal(edge);
+    (-pr * n.x, -pr * n.y)
+}
+
+pub fn node_normal(x: Point, p: Point, q: Point, curve: &Edge) -> Point {
+    // unit outward normal at the node x of the segment p -> q cut from curve
+    // on arcs this is the radius through the node, which differs from node to node
+    let chord = (p - q).perp();
+    let chord = chord / chord.dist(Point::origin());
+
+    match *curve {
+        Edge::Arc(a) => {
+            let r = x - a.center();
+            let r = r / r.dist(Point::origin());
+            if r.x * chord.x + r.y * chord.y < 0.0 {
+                r * -1.0
+            } else {
+                r
+            }
+        }
+        Edge::Segment(_) => chord,
+    }
+}
+
+pub fn curve_deviation(p: Point, q: Point, curve: &Edge) -> f64 {
+    // furthest distance of the segment's endpoints from the curve it was cut from
+    let off = |x: Point| match *curve {
+        Edge::Arc(a) => (x.dist(a.center()) - a.radius()).abs(),
+        Edge::Segment(_) => {
+            let d = curve.q() - curve.p();
+            let v = x - curve.p();
+            (d.x * v.y - d.y * v.x).abs() / d.dist(Point::origin())
+        }
+    };
+
+    off(p).max(off(q))
+}
@@ -1,9 +1,56 @@
 mod app;
+mod export;
+mod geom;
+mod material;
 mod reader;
 mod writer;
 
 use iced::{Sandbox, Settings};
 
+use app::mark::MarkedModel;
+use reader::PartModel;
+
 fn main() -> iced::Result {
-    app::LacoApp::run(Settings::default())
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // headless geometry conversions: bugi_laco <format> <source> <output> [unit] [segment length]
+    // there is no way to annotate here, so these write the bare outline; export from the app
+    // for the constraints and loads
+    match args.as_slice() {
+        [] => app::LacoApp::run(Settings::default()),
+        [format, source, output, rest @ ..]
+            if (format == "svg" || format == "dxf") && rest.len() <= 2 =>
+        {
+            // the unit only tags the dxf header, svg has none
+            let unit = match rest.first() {
+                None => None,
+                Some(u) => match app::Unit::ALL.into_iter().find(|x| x.to_string() == *u) {
+                    Some(x) => Some(x),
+                    None => usage(),
+                },
+            };
+
+            let mut model: MarkedModel = PartModel::load(source).into();
+            if let Some(len) = rest.get(1) {
+                match len.parse() {
+                    Ok(len) => model.segmentify(len),
+                    Err(_) => usage(),
+                }
+            }
+
+            if format == "svg" {
+                export::svg::write_svg(&model, output);
+            } else {
+                let units = unit.map_or(dxf::enums::Units::Unitless, |u| u.dxf_units());
+                export::dxf::write_dxf(&model, output, units);
+            }
+            Ok(())
+        }
+        _ => usage(),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("usage: bugi_laco [svg|dxf <source> <output> [m|mm|in] [segment length]]");
+    std::process::exit(1);
 }
@@ -0,0 +1,174 @@
+use std::path::Path;
+
+// materials the user can pick for a part, all in SI units
+// the library is the built-ins below plus anything in USER_FILE, which wins on a name clash
+pub const USER_FILE: &str = "materials.txt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    pub youngs_modulus: f64,
+    pub poisson_ratio: f64,
+    pub density: f64,
+    pub yield_stress: f64,
+}
+
+// name, E (Pa), nu, density (kg/m^3), yield stress (Pa)
+const BUILT_IN: [(&str, f64, f64, f64, f64); 10] = [
+    ("steel_s235", 210e9, 0.30, 7850.0, 235e6),
+    ("steel_s355", 210e9, 0.30, 7850.0, 355e6),
+    ("steel_4140", 205e9, 0.29, 7850.0, 655e6),
+    ("stainless_304", 193e9, 0.29, 8000.0, 215e6),
+    ("aluminium_6061_t6", 68.9e9, 0.33, 2700.0, 276e6),
+    ("aluminium_7075_t6", 71.7e9, 0.33, 2810.0, 503e6),
+    ("titanium_ti6al4v", 113.8e9, 0.34, 4430.0, 880e6),
+    ("abs", 2.3e9, 0.35, 1050.0, 40e6),
+    ("nylon_pa6", 2.8e9, 0.39, 1140.0, 70e6),
+    ("polycarbonate", 2.4e9, 0.37, 1200.0, 62e6),
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct Library(Vec<Material>);
+
+impl Library {
+    pub fn built_in() -> Self {
+        Self(
+            BUILT_IN
+                .iter()
+                .map(|&(name, e, nu, rho, sy)| Material {
+                    name: name.to_string(),
+                    youngs_modulus: e,
+                    poisson_ratio: nu,
+                    density: rho,
+                    yield_stress: sy,
+                })
+                .collect(),
+        )
+    }
+
+    pub fn load() -> Self {
+        // built-ins plus the user's file when there is one
+        let mut lib = Self::built_in();
+
+        if Path::new(USER_FILE).exists() {
+            let text = std::fs::read_to_string(USER_FILE).unwrap();
+            for m in parse_materials(&text) {
+                lib.insert(m);
+            }
+        }
+
+        lib
+    }
+
+    fn insert(&mut self, m: Material) {
+        match self.0.iter_mut().find(|x| x.name == m.name) {
+            Some(x) => *x = m,
+            None => self.0.push(m),
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().map(|m| m.name.clone()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Material> {
+        self.0.iter().find(|m| m.name == name)
+    }
+}
+
+fn parse_materials(text: &str) -> Vec<Material> {
+    // one material per line: name E nu density yield, '#' starts a comment
+    let mut res = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let vals: Option<Vec<f64>> = fields.iter().skip(1).map(|f| f.parse().ok()).collect();
+
+        match vals {
+            Some(v) if v.len() == 4 => {
+                if v[0] <= 0.0 || v[1] <= -1.0 || v[1] >= 0.5 {
+                    eprintln!("WARNING: {} line {}: implausible E or nu", USER_FILE, i + 1);
+                    continue;
+                }
+
+                res.push(Material {
+                    name: fields[0].to_string(),
+                    youngs_modulus: v[0],
+                    poisson_ratio: v[1],
+                    density: v[2],
+                    yield_stress: v[3],
+                });
+            }
+            _ => eprintln!(
+                "WARNING: {} line {}: expected \"name E nu density yield\"",
+                USER_FILE,
+                i + 1
+            ),
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_materials() {
+        let text = "\
+# name E nu density yield
+steel 200e9 0.3 7800 250e6
+
+ali 70e9 0.33 2700 200e6  # trailing comment
+";
+        let ms = parse_materials(text);
+
+        assert_eq!(ms.len(), 2);
+        assert_eq!(
+            ms[0],
+            Material {
+                name: "steel".to_string(),
+                youngs_modulus: 200e9,
+                poisson_ratio: 0.3,
+                density: 7800.0,
+                yield_stress: 250e6,
+            }
+        );
+        assert_eq!(ms[1].name, "ali");
+    }
+
+    #[test]
+    fn skips_bad_lines() {
+        let text = "\
+short 1 2
+words steel 0.3 7800 250e6
+negative -1 0.3 7800 250e6
+incompressible 1e9 0.5 1000 1e6
+good 1e9 0.3 1000 1e6
+";
+        let ms = parse_materials(text);
+
+        assert_eq!(ms.len(), 1);
+        assert_eq!(ms[0].name, "good");
+    }
+
+    #[test]
+    fn user_materials_win() {
+        let mut lib = Library::built_in();
+        let n = lib.names().len();
+
+        for m in parse_materials("steel_s235 1 0.3 1 1\nnew 1 0.3 1 1") {
+            lib.insert(m);
+        }
+
+        assert_eq!(lib.names().len(), n + 1);
+        assert_eq!(lib.get("steel_s235").unwrap().youngs_modulus, 1.0);
+        assert!(lib.get("new").is_some());
+    }
+}
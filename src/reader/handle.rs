@@ -11,6 +11,9 @@ fn dxf_point(p: &dxf::Point) -> Point {
     Point::new(p.x, p.y)
 }
 
+// points closer than this are the same point
+pub const POINT_TOLERANCE: f64 = 1e-6;
+
 #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub struct PointId(usize);
 
@@ -33,8 +36,7 @@ impl PointStore {
         // quadtrees are probably the right way to go about this
 
         for (id, stored_p) in self.0.iter() {
-            // ad hoc tolerance
-            if stored_p.dist(p) < 1e-6 {
+            if stored_p.dist(p) < POINT_TOLERANCE {
                 return *id;
             }
         }
@@ -113,6 +115,13 @@ impl EdgeHandle {
         }
     }
 
+    pub fn from_edge(store: &mut PointStore, edge: Edge) -> Self {
+        let p_id = store.id_or_insert(edge.p());
+        let q_id = store.id_or_insert(edge.q());
+
+        Self { edge, p_id, q_id }
+    }
+
     pub fn p_id(&self) -> PointId {
         self.p_id
     }
@@ -1,4 +1,6 @@
 mod handle;
+mod points;
+mod svg;
 
 use handle::{EdgeHandle, PointStore};
 
@@ -8,11 +10,32 @@ use std::path::Path;
 
 #[derive(Clone, Debug)]
 pub struct PartModel {
+    pub(crate) regions: Vec<Region>,
+}
+
+// one connected piece of material: an outer bound and the holes in it
+#[derive(Clone, Debug)]
+pub struct Region {
     pub(crate) outer_bound: Boundary,
     pub(crate) inner_bounds: Vec<Boundary>,
 }
 
 impl PartModel {
+    pub fn load<T: AsRef<Path>>(source: T) -> Self {
+        // pick a loader by file extension, dxf unless told otherwise
+        let ext = source
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+
+        match ext.as_deref() {
+            Some("svg") => Self::load_svg(source),
+            Some("csv") | Some("txt") => Self::load_points(source),
+            _ => Self::load_dxf(source),
+        }
+    }
+
     pub fn load_dxf<T: AsRef<Path>>(source: T) -> Self {
         let input = dxf::Drawing::load_file(source).unwrap();
 
@@ -24,6 +47,28 @@ impl PartModel {
             edges.extend(EdgeHandle::from_entity(&mut store, ent))
         }
 
+        Self::from_edges(edges)
+    }
+
+    pub fn load_svg<T: AsRef<Path>>(source: T) -> Self {
+        let input = std::fs::read_to_string(source).unwrap();
+
+        let mut store = PointStore::new();
+        let edges = svg::edges_from_svg(&mut store, &input);
+
+        Self::from_edges(edges)
+    }
+
+    pub fn load_points<T: AsRef<Path>>(source: T) -> Self {
+        let input = std::fs::read_to_string(source).unwrap();
+
+        let mut store = PointStore::new();
+        let edges = points::edges_from_points(&mut store, &input);
+
+        Self::from_edges(edges)
+    }
+
+    fn from_edges(mut edges: Vec<EdgeHandle>) -> Self {
         // group segments into boundaries
         let mut bounds: Vec<Boundary> = Vec::new();
 
@@ -55,39 +100,43 @@ impl PartModel {
             bounds.push(Boundary::new(bound_edges));
         }
 
-        // one of the bounds will enclose all bounds
-        // all others will be enclosed by that one and no others
-
-        let mut enclosing = None;
-
-        for i in 0..bounds.len() {
-            let cur_bound = bounds.get(i).unwrap();
-
-            // check if all but the current boundary are enclosed
-            let mut all_but_cur = bounds
-                .iter()
-                .enumerate()
-                .filter(|&(j, _)| j != i)
-                .map(|(_, v)| v);
-            if all_but_cur.all(|b| cur_bound.contains_boundary(b)) {
-                enclosing = Some(i);
-            }
+        assert!(!bounds.is_empty(), "no closed boundary");
+
+        // loops nest: inside an even number of others a loop is the outer bound of a region,
+        // inside an odd number it is a hole in the loop directly around it
+        let n = bounds.len();
+        let contains = |j: usize, i: usize| j != i && bounds[j].contains_boundary(&bounds[i]);
+        let depth: Vec<usize> = (0..n)
+            .map(|i| (0..n).filter(|&j| contains(j, i)).count())
+            .collect();
+
+        let mut regions = Vec::new();
+        let mut region_of = vec![0; n];
+
+        // store outer bounds in positive orientation and inner bounds in negative orientation
+        for i in (0..n).filter(|&i| depth[i] % 2 == 0) {
+            let mut outer_bound = bounds[i].clone();
+            outer_bound.orient_positive();
+
+            region_of[i] = regions.len();
+            regions.push(Region {
+                outer_bound,
+                inner_bounds: Vec::new(),
+            });
         }
 
-        let mut enclosing = bounds.swap_remove(enclosing.expect("no enclosing boundary"));
+        for i in (0..n).filter(|&i| depth[i] % 2 == 1) {
+            let owner = (0..n)
+                .find(|&j| depth[j] + 1 == depth[i] && contains(j, i))
+                .expect("hole without an enclosing boundary");
 
-        // store outer bound in positive orientation and inner bounds in negative orientation
-        enclosing.orient_positive();
-        for b in bounds.iter_mut() {
-            b.orient_negative();
+            let mut inner_bound = bounds[i].clone();
+            inner_bound.orient_negative();
+            regions[region_of[owner]].inner_bounds.push(inner_bound);
         }
 
-        // TODO: handle improper models where a boundary is enclosed by more than one (nested)
         // TODO: check that no boundaries intersect
 
-        Self {
-            outer_bound: enclosing,
-            inner_bounds: bounds,
-        }
+        Self { regions }
     }
 }
@@ -0,0 +1,124 @@
+use spacemath::two::dist::Dist;
+use spacemath::two::line::Segment;
+use spacemath::two::point::Point;
+
+use super::handle::{EdgeHandle, PointStore, POINT_TOLERANCE};
+
+// reads closed polylines from plain text: one "x, y" (or "x y", "x;y", "x\ty") pair
+// per line, loops separated by blank lines, '#' starting a comment
+pub fn edges_from_points(store: &mut PointStore, input: &str) -> Vec<EdgeHandle> {
+    let mut edges = Vec::new();
+    let mut points: Vec<Point> = Vec::new();
+
+    for (i, line) in input.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+
+        if line.is_empty() {
+            close_loop(store, &mut edges, &mut points);
+            continue;
+        }
+
+        match parse_point(line) {
+            // a repeated point would make a zero length edge
+            Some(p) if points.last().map_or(false, |l| l.dist(p) < POINT_TOLERANCE) => (),
+            Some(p) => points.push(p),
+            // headers are expected, anything later is worth flagging
+            None if points.is_empty() => {
+                eprintln!("WARNING: skipping header line {}: {}", i + 1, line)
+            }
+            None => eprintln!("WARNING: skipping ill-formed line {}: {}", i + 1, line),
+        }
+    }
+
+    close_loop(store, &mut edges, &mut points);
+
+    edges
+}
+
+fn parse_point(line: &str) -> Option<Point> {
+    let mut vals = line
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|v| !v.is_empty());
+
+    let x = vals.next()?.parse().ok()?;
+    let y = vals.next()?.parse().ok()?;
+
+    if vals.next().is_some() {
+        return None;
+    }
+
+    Some(Point::new(x, y))
+}
+
+fn close_loop(store: &mut PointStore, edges: &mut Vec<EdgeHandle>, points: &mut Vec<Point>) {
+    // the loop may or may not repeat its first point at the end
+    if points.len() > 1 && points.first().unwrap().dist(*points.last().unwrap()) < POINT_TOLERANCE {
+        points.pop();
+    }
+
+    if points.len() >= 3 {
+        for i in 0..points.len() {
+            let seg = Segment::new(points[i], points[(i + 1) % points.len()]);
+            edges.push(EdgeHandle::from_edge(store, seg.into()));
+        }
+    } else if !points.is_empty() {
+        eprintln!("WARNING: skipping loop with fewer than 3 points");
+    }
+
+    points.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use spacemath::two::boundary::Edge;
+
+    #[test]
+    fn point_separators() {
+        for line in ["1, 2", "1 2", "1;2", "1\t2", "1 ,  2"] {
+            let p = parse_point(line).unwrap();
+            assert_eq!((p.x, p.y), (1.0, 2.0), "{}", line);
+        }
+
+        assert!(parse_point("1, 2, 3").is_none());
+        assert!(parse_point("x, y").is_none());
+        assert!(parse_point("1").is_none());
+    }
+
+    #[test]
+    fn loops() {
+        // a header, a repeated point and an explicit return to the start in the first loop
+        let input = "\
+x, y
+0, 0
+1, 0
+1, 0
+1, 1   # corner
+0, 0
+
+5 5
+6 5
+6 6
+";
+        let mut store = PointStore::new();
+        let edges: Vec<Edge> = edges_from_points(&mut store, input)
+            .into_iter()
+            .map(|e| e.into())
+            .collect();
+
+        assert_eq!(edges.len(), 6);
+        assert!(edges.iter().all(|e| e.p().dist(e.q()) > POINT_TOLERANCE));
+
+        // each loop closes on its first point
+        assert_eq!(edges[2].q().dist(edges[0].p()), 0.0);
+        assert_eq!(edges[5].q().dist(edges[3].p()), 0.0);
+    }
+
+    #[test]
+    fn short_loops_are_skipped() {
+        let mut store = PointStore::new();
+
+        assert!(edges_from_points(&mut store, "0, 0\n1, 0\n1, 0\n").is_empty());
+    }
+}
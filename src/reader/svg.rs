@@ -0,0 +1,289 @@
+use std::str::FromStr;
+
+use spacemath::two::boundary::Edge;
+use spacemath::two::dist::Dist;
+use spacemath::two::line::{Arc, Segment};
+use spacemath::two::point::Point;
+
+use svgtypes::{SimplePathSegment, SimplifyingPathParser};
+
+use super::handle::{EdgeHandle, PointStore, POINT_TOLERANCE};
+
+// beziers are flattened until the control polygon is within this fraction of its length
+const FLATNESS: f64 = 1e-3;
+
+// number of segments used for ellipses (and circles under non-uniform transforms)
+const ELLIPSE_SEGMENTS: usize = 64;
+
+// 2d affine map [a, b, c, d, e, f] as in svg: x' = ax + cy + e, y' = bx + dy + f
+#[derive(Clone, Copy, Debug)]
+struct Affine([f64; 6]);
+
+impl Affine {
+    const IDENTITY: Affine = Affine([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    fn parse(x: Option<&str>) -> Self {
+        match x.map(svgtypes::Transform::from_str) {
+            Some(Ok(t)) => Affine([t.a, t.b, t.c, t.d, t.e, t.f]),
+            Some(Err(e)) => {
+                eprintln!("WARNING: ignoring ill-formed svg transform: {}", e);
+                Self::IDENTITY
+            }
+            None => Self::IDENTITY,
+        }
+    }
+
+    fn then(self, inner: Affine) -> Self {
+        // the map applying inner first, then self
+        let [a, b, c, d, e, f] = self.0;
+        let [ia, ib, ic, id, ie, i_f] = inner.0;
+
+        Affine([
+            a * ia + c * ib,
+            b * ia + d * ib,
+            a * ic + c * id,
+            b * ic + d * id,
+            a * ie + c * i_f + e,
+            b * ie + d * i_f + f,
+        ])
+    }
+
+    fn apply(&self, x: f64, y: f64) -> Point {
+        // svg is y-down, model space is y-up
+        let [a, b, c, d, e, f] = self.0;
+        Point::new(a * x + c * y + e, -1.0 * (b * x + d * y + f))
+    }
+
+    fn uniform_scale(&self) -> Option<f64> {
+        // the scale factor if this map keeps circles circular
+        let [a, b, c, d, _, _] = self.0;
+        let sx = (a * a + b * b).sqrt();
+        let sy = (c * c + d * d).sqrt();
+
+        let orthogonal = (a * c + b * d).abs() < 1e-9 * sx * sy;
+        if orthogonal && (sx - sy).abs() < 1e-9 * sx {
+            Some(sx)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn edges_from_svg(store: &mut PointStore, input: &str) -> Vec<EdgeHandle> {
+    let doc = roxmltree::Document::parse(input).expect("ill-formed svg");
+
+    let mut edges = Vec::new();
+    collect_node(store, doc.root_element(), Affine::IDENTITY, &mut edges);
+
+    edges
+}
+
+fn collect_node(
+    store: &mut PointStore,
+    node: roxmltree::Node,
+    parent: Affine,
+    edges: &mut Vec<EdgeHandle>,
+) {
+    let transform = parent.then(Affine::parse(node.attribute("transform")));
+
+    let new_edges = match node.tag_name().name() {
+        "svg" | "g" => {
+            for child in node.children().filter(|n| n.is_element()) {
+                collect_node(store, child, transform, edges);
+            }
+            Vec::new()
+        }
+        "path" => path_edges(node.attribute("d").unwrap_or(""), &transform),
+        "rect" => rect_edges(node, &transform),
+        "circle" => ellipse_edges(
+            (num_attr(node, "cx"), num_attr(node, "cy")),
+            (num_attr(node, "r"), num_attr(node, "r")),
+            &transform,
+        ),
+        "ellipse" => ellipse_edges(
+            (num_attr(node, "cx"), num_attr(node, "cy")),
+            (num_attr(node, "rx"), num_attr(node, "ry")),
+            &transform,
+        ),
+        // defs, style, metadata etc. carry no geometry of their own
+        _ => Vec::new(),
+    };
+
+    for e in new_edges {
+        edges.push(EdgeHandle::from_edge(store, e));
+    }
+}
+
+fn num_attr(node: roxmltree::Node, name: &str) -> f64 {
+    node.attribute(name)
+        .and_then(|v| svgtypes::Number::from_str(v).ok())
+        .map(|n| n.0)
+        .unwrap_or(0.0)
+}
+
+fn rect_edges(node: roxmltree::Node, transform: &Affine) -> Vec<Edge> {
+    let (x, y) = (num_attr(node, "x"), num_attr(node, "y"));
+    let (w, h) = (num_attr(node, "width"), num_attr(node, "height"));
+
+    if node.attribute("rx").is_some() || node.attribute("ry").is_some() {
+        eprintln!("WARNING: ignoring rounded corners on svg rect");
+    }
+
+    let corners = [
+        transform.apply(x, y),
+        transform.apply(x + w, y),
+        transform.apply(x + w, y + h),
+        transform.apply(x, y + h),
+    ];
+
+    polygon_edges(&corners)
+}
+
+fn ellipse_edges(c: (f64, f64), r: (f64, f64), transform: &Affine) -> Vec<Edge> {
+    if (r.0 - r.1).abs() < 1e-12 {
+        if let Some(scale) = transform.uniform_scale() {
+            // a true circle survives as a single full arc
+            let center = transform.apply(c.0, c.1);
+            let radius = r.0 * scale;
+            let arc = Arc::from_center_ang(center, radius, 0.0, std::f64::consts::TAU, true);
+
+            return vec![arc.into()];
+        }
+    }
+
+    let points: Vec<Point> = (0..ELLIPSE_SEGMENTS)
+        .map(|i| {
+            let t = std::f64::consts::TAU * (i as f64) / (ELLIPSE_SEGMENTS as f64);
+            transform.apply(c.0 + r.0 * t.cos(), c.1 + r.1 * t.sin())
+        })
+        .collect();
+
+    polygon_edges(&points)
+}
+
+fn polygon_edges(points: &[Point]) -> Vec<Edge> {
+    (0..points.len())
+        .map(|i| Segment::new(points[i], points[(i + 1) % points.len()]).into())
+        .collect()
+}
+
+fn path_edges(d: &str, transform: &Affine) -> Vec<Edge> {
+    // the simplifying parser makes every command absolute and turns
+    // arcs and shorthand curves into plain cubic / quadratic curves
+    let mut edges = Vec::new();
+
+    // points of the current subpath, in svg user space
+    let mut subpath: Vec<(f64, f64)> = Vec::new();
+    let mut closed = false;
+
+    for seg in SimplifyingPathParser::from(d) {
+        let seg = match seg {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("WARNING: stopping at ill-formed svg path data: {}", e);
+                break;
+            }
+        };
+
+        match seg {
+            SimplePathSegment::MoveTo { x, y } => {
+                finish_subpath(&mut edges, &subpath, closed, transform);
+                subpath = vec![(x, y)];
+                closed = false;
+            }
+            SimplePathSegment::LineTo { x, y } => {
+                subpath.push((x, y));
+            }
+            SimplePathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => {
+                let p0 = *subpath.last().unwrap();
+                flatten_cubic(&mut subpath, [p0, (x1, y1), (x2, y2), (x, y)]);
+            }
+            SimplePathSegment::Quadratic { x1, y1, x, y } => {
+                // elevate to a cubic with the same shape
+                let p0 = *subpath.last().unwrap();
+                let c1 = (
+                    p0.0 + 2.0 / 3.0 * (x1 - p0.0),
+                    p0.1 + 2.0 / 3.0 * (y1 - p0.1),
+                );
+                let c2 = (x + 2.0 / 3.0 * (x1 - x), y + 2.0 / 3.0 * (y1 - y));
+                flatten_cubic(&mut subpath, [p0, c1, c2, (x, y)]);
+            }
+            SimplePathSegment::ClosePath => {
+                closed = true;
+            }
+        }
+    }
+
+    finish_subpath(&mut edges, &subpath, closed, transform);
+
+    edges
+}
+
+fn finish_subpath(edges: &mut Vec<Edge>, subpath: &[(f64, f64)], closed: bool, transform: &Affine) {
+    if subpath.len() < 2 {
+        return;
+    }
+
+    let mut points: Vec<Point> = subpath
+        .iter()
+        .map(|&(x, y)| transform.apply(x, y))
+        .collect();
+
+    // a line to the current point (or a degenerate curve) would make a zero length edge
+    points.dedup_by(|a, b| a.dist(*b) < POINT_TOLERANCE);
+
+    // drop an explicit return to the start, polygon_edges closes the loop itself
+    if points.first().unwrap().dist(*points.last().unwrap()) < POINT_TOLERANCE {
+        points.pop();
+    } else if !closed {
+        eprintln!("WARNING: treating open svg subpath as closed");
+    }
+
+    if points.len() < 3 {
+        eprintln!("WARNING: skipping svg subpath with fewer than 3 points");
+        return;
+    }
+
+    edges.extend(polygon_edges(&points));
+}
+
+fn flatten_cubic(out: &mut Vec<(f64, f64)>, ctrl: [(f64, f64); 4]) {
+    // recursive de casteljau subdivision, pushes every point after the first
+    let chord = dist(ctrl[0], ctrl[3]);
+    let poly = dist(ctrl[0], ctrl[1]) + dist(ctrl[1], ctrl[2]) + dist(ctrl[2], ctrl[3]);
+
+    flatten_rec(out, ctrl, FLATNESS * poly.max(chord), 0);
+}
+
+fn flatten_rec(out: &mut Vec<(f64, f64)>, ctrl: [(f64, f64); 4], tol: f64, depth: usize) {
+    let chord = dist(ctrl[0], ctrl[3]);
+    let poly = dist(ctrl[0], ctrl[1]) + dist(ctrl[1], ctrl[2]) + dist(ctrl[2], ctrl[3]);
+
+    if poly - chord <= tol || depth > 16 {
+        out.push(ctrl[3]);
+        return;
+    }
+
+    let mid = |a: (f64, f64), b: (f64, f64)| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+
+    let p01 = mid(ctrl[0], ctrl[1]);
+    let p12 = mid(ctrl[1], ctrl[2]);
+    let p23 = mid(ctrl[2], ctrl[3]);
+    let p012 = mid(p01, p12);
+    let p123 = mid(p12, p23);
+    let m = mid(p012, p123);
+
+    flatten_rec(out, [ctrl[0], p01, p012, m], tol, depth + 1);
+    flatten_rec(out, [m, p123, p23, ctrl[3]], tol, depth + 1);
+}
+
+fn dist(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
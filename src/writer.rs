@@ -2,14 +2,65 @@ use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::path::Path;
 
+use spacemath::two::boundary::Edge;
+use spacemath::two::line::Segment;
 use spacemath::two::Point;
 
+use crate::app::body::BodyLoads;
 use crate::app::mark::{Annotation, MarkedBound};
+use crate::geom::{curve_deviation, edge_length, node_normal, pressure_traction};
+use crate::material::Material;
+
+// how far (relative to the curve's length) a tangent-constrained edge's nodes may be off
+// the curve it was cut from
+const CURVE_TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Condition {
+    #[default]
+    PlaneStress,
+    PlaneStrain,
+    // revolved about the y axis, x is the radius
+    Axisymmetric,
+}
+
+impl Condition {
+    pub const ALL: [Condition; 3] = [
+        Condition::PlaneStress,
+        Condition::PlaneStrain,
+        Condition::Axisymmetric,
+    ];
+
+    pub fn needs_thickness(self) -> bool {
+        self != Condition::Axisymmetric
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Condition::PlaneStress => "plane stress",
+                Condition::PlaneStrain => "plane strain",
+                Condition::Axisymmetric => "axisymmetric",
+            }
+        )
+    }
+}
 
 pub struct Writer {
-    points: Vec<Vec<(String, Option<String>)>>,
+    // polygons tagged with their region
+    points: Vec<(usize, Vec<(String, Option<String>)>)>,
     constraints: Vec<((String, String), String)>,
     forces: Vec<((String, String), (f64, f64))>,
+    displacements: Vec<((String, String), String)>,
+    springs: Vec<((String, String), (f64, f64))>,
+    point_constraints: Vec<(String, String)>,
+    point_forces: Vec<(String, (f64, f64))>,
+
+    body_loads: BodyLoads,
 
     // for unit conversions (bbnd is meters)
     scale: f64,
@@ -21,6 +72,11 @@ impl Writer {
             points: Vec::new(),
             constraints: Vec::new(),
             forces: Vec::new(),
+            displacements: Vec::new(),
+            springs: Vec::new(),
+            point_constraints: Vec::new(),
+            point_forces: Vec::new(),
+            body_loads: BodyLoads::default(),
 
             scale: 1.0,
         }
@@ -30,8 +86,13 @@ impl Writer {
         Self { scale, ..self }
     }
 
+    pub fn body_loads(self, body_loads: BodyLoads) -> Self {
+        Self { body_loads, ..self }
+    }
+
     pub fn add_boundary(&mut self, marked_bound: MarkedBound) {
-        let (bound, marks) = marked_bound.into_parts();
+        let region = marked_bound.region();
+        let (bound, marks, vertex_marks, parents) = marked_bound.into_parts();
 
         let points_raw = bound.points().into_iter().collect::<Vec<Point>>();
 
@@ -46,71 +107,171 @@ impl Writer {
             .collect();
 
         // treats all edges as segments. Break arcs down before this point
-        for (vs, mark) in edge_vertices.iter().zip(marks) {
-            if mark.is_force() {
-                // both the edge's vertices get used
-                let p_label = point_label(&points[vs.0].0);
-                let q_label = point_label(&points[vs.1].0);
-
-                // some duplicate computation here but oh well
-                points[vs.0].1 = Some(p_label.clone());
-                points[vs.1].1 = Some(q_label.clone());
-
-                let f = match mark.annot {
-                    Annotation::Force(x, y) => (x, y),
-                    _ => unreachable!(),
-                };
+        for ((vs, mark), parent) in edge_vertices.iter().zip(marks).zip(parents.iter()) {
+            if !mark.is_annotated() {
+                continue;
+            }
 
-                self.forces.push(((p_label, q_label), f));
-            } else if mark.is_constraint() {
-                let p_label = point_label(&points[vs.0].0);
-                let q_label = point_label(&points[vs.1].0);
+            // both the edge's vertices get used
+            let p_label = point_label(&points[vs.0].0);
+            let q_label = point_label(&points[vs.1].0);
 
-                points[vs.0].1 = Some(p_label.clone());
-                points[vs.1].1 = Some(q_label.clone());
+            // some duplicate computation here but oh well
+            points[vs.0].1 = Some(p_label.clone());
+            points[vs.1].1 = Some(q_label.clone());
 
-                let c = match mark.annot {
-                    Annotation::ConstrainX => "x".to_string(),
-                    Annotation::ConstrainY => "y".to_string(),
-                    Annotation::ConstrainXY => "xy".to_string(),
+            for annot in mark.annots {
+                let labels = (p_label.clone(), q_label.clone());
+
+                match annot {
+                    Annotation::Force(x, y) => self.forces.push((labels, (x, y))),
+                    Annotation::Pressure(pr) => {
+                        // resolve into a traction along the inward normal of this segment
+                        let seg = Segment::new(points_raw[vs.0], points_raw[vs.1]).into();
+                        self.forces.push((labels, pressure_traction(pr, &seg)));
+                    }
+                    Annotation::Profile(pl) => {
+                        let mid = points_raw[vs.0].mid(points_raw[vs.1]);
+                        self.forces.push((labels, pl.traction_at(mid)));
+                    }
+                    Annotation::ConstrainX => self.constraints.push((labels, "x".to_string())),
+                    Annotation::ConstrainY => self.constraints.push((labels, "y".to_string())),
+                    Annotation::ConstrainXY => self.constraints.push((labels, "xy".to_string())),
                     Annotation::ConstrainTangent => {
-                        let ang = (points_raw[vs.0] - points_raw[vs.1]).perp().ang();
-                        format!("angle:{:3.5}", ang)
+                        let (p, q) = (points_raw[vs.0], points_raw[vs.1]);
+
+                        // the normals come from parent, so an edge that isn't on it would be
+                        // held in the wrong direction
+                        if curve_deviation(p, q, parent) > CURVE_TOLERANCE * edge_length(parent) {
+                            eprintln!(
+                                "WARNING: leaving out the tangent constraint on {} {}, off its curve",
+                                labels.0, labels.1
+                            );
+                            continue;
+                        }
+
+                        match parent {
+                            // cut from an arc, the normal turns along the edge so each node
+                            // is held along the radius through it
+                            Edge::Arc(_) => {
+                                for (x, label) in [(p, labels.0), (q, labels.1)] {
+                                    let ang = node_normal(x, p, q, parent).ang();
+                                    let c = (label, format!("angle:{:3.5}", ang));
+                                    if !self.point_constraints.contains(&c) {
+                                        self.point_constraints.push(c);
+                                    }
+                                }
+                            }
+                            Edge::Segment(_) => {
+                                let ang = node_normal(p, p, q, parent).ang();
+                                self.constraints
+                                    .push((labels, format!("angle:{:3.5}", ang)));
+                            }
+                        }
                     }
-                    _ => unreachable!(),
-                };
+                    Annotation::Displacement(dx, dy) => {
+                        let d = match (dx, dy) {
+                            (Some(x), None) => format!("x {}", x * self.scale),
+                            (None, Some(y)) => format!("y {}", y * self.scale),
+                            (Some(x), Some(y)) => {
+                                format!("xy {} {}", x * self.scale, y * self.scale)
+                            }
+                            (None, None) => unreachable!(),
+                        };
+                        self.displacements.push((labels, d));
+                    }
+                    Annotation::Spring(kn, kt) => self.springs.push((labels, (kn, kt))),
+                    Annotation::Empty => unreachable!(),
+                }
+            }
+        }
+
+        // vertex i is the start of edge i, so it shares the point's index
+        for (i, mark) in vertex_marks.into_iter().enumerate() {
+            if !mark.is_annotated() {
+                continue;
+            }
+
+            let label = point_label(&points[i].0);
+            points[i].1 = Some(label.clone());
 
-                self.constraints.push(((p_label, q_label), c));
+            for annot in mark.annots {
+                let l = label.clone();
+
+                match annot {
+                    Annotation::Force(x, y) => self.point_forces.push((l, (x, y))),
+                    Annotation::ConstrainX => self.point_constraints.push((l, "x".to_string())),
+                    Annotation::ConstrainY => self.point_constraints.push((l, "y".to_string())),
+                    Annotation::ConstrainXY => self.point_constraints.push((l, "xy".to_string())),
+                    _ => unreachable!(),
+                }
             }
         }
 
-        self.points.push(points);
+        self.points.push((region, points));
     }
 
-    pub fn write<T: AsRef<Path>>(self, path: T, material: &str, thickness: f64) {
+    pub fn write<T: AsRef<Path>>(self, path: T, condition: Condition, regions: &[(Material, f64)]) {
+        // regions holds the material and thickness (in meters) of each region
         let mut to_write = String::new();
 
-        // write all the polygons
-        for polygon in self.points {
-            to_write.push_str("polygon\n");
+        // spell out each material used so bugi doesn't depend on its own library,
+        // name then E (Pa), nu, density (kg/m^3) and yield stress (Pa)
+        let mut written: Vec<&str> = Vec::new();
+        for (m, _) in regions.iter() {
+            if written.contains(&m.name.as_str()) {
+                continue;
+            }
+            written.push(&m.name);
 
-            for (point, label) in polygon {
-                to_write.push_str(&point);
+            to_write.push_str(&format!(
+                "material_properties {} {} {} {} {}\n",
+                m.name, m.youngs_modulus, m.poisson_ratio, m.density, m.yield_stress
+            ));
+        }
 
-                if let Some(l) = label {
-                    to_write.push(' ');
-                    to_write.push_str(&l);
+        // write all the polygons, grouped under their region's properties when there are several
+        for (r, (material, thickness)) in regions.iter().enumerate() {
+            if regions.len() > 1 {
+                if condition.needs_thickness() {
+                    to_write.push_str(&format!("region {} {}\n", material.name, thickness));
+                } else {
+                    to_write.push_str(&format!("region {}\n", material.name));
                 }
-
-                to_write.push('\n');
             }
 
-            to_write.push_str("end\n");
+            for (_, polygon) in self.points.iter().filter(|(pr, _)| *pr == r) {
+                to_write.push_str("polygon\n");
+
+                for (point, label) in polygon {
+                    to_write.push_str(point);
+
+                    if let Some(l) = label {
+                        to_write.push(' ');
+                        to_write.push_str(l);
+                    }
+
+                    to_write.push('\n');
+                }
+
+                to_write.push_str("end\n");
+            }
         }
 
-        // fill in proper material selection logic (with user input somewhere)
-        to_write.push_str(&format!("condition planestress {}\n", thickness));
-        to_write.push_str(&format!("material {}\n", material));
+        // the first region's properties are the defaults
+        let (material, thickness) = &regions[0];
+
+        // axisymmetric models have no thickness, bugi integrates around the axis
+        match condition {
+            Condition::PlaneStress => {
+                to_write.push_str(&format!("condition planestress {}\n", thickness))
+            }
+            Condition::PlaneStrain => {
+                to_write.push_str(&format!("condition planestrain {}\n", thickness))
+            }
+            Condition::Axisymmetric => to_write.push_str("condition axisymmetric\n"),
+        }
+        to_write.push_str(&format!("material {}\n", material.name));
 
         // write all the constraints
         for ((p_label, q_label), c) in self.constraints {
@@ -118,6 +279,21 @@ impl Writer {
             to_write.push_str(&c);
         }
 
+        // write all the prescribed displacements, in meters like the geometry
+        for ((p_label, q_label), d) in self.displacements {
+            let d = format!("distributed_displacement {} {} {}\n", p_label, q_label, d);
+            to_write.push_str(&d);
+        }
+
+        // write all the elastic supports, normal then tangential stiffness
+        for ((p_label, q_label), k) in self.springs {
+            let k = format!(
+                "distributed_spring {} {} {} {}\n",
+                p_label, q_label, k.0, k.1
+            );
+            to_write.push_str(&k);
+        }
+
         // write all the forces
         for ((p_label, q_label), f) in self.forces {
             let f = format!(
@@ -127,6 +303,28 @@ impl Writer {
             to_write.push_str(&f);
         }
 
+        // write the point-level conditions
+        for (label, c) in self.point_constraints {
+            to_write.push_str(&format!("point_constraint {} {}\n", label, c));
+        }
+
+        for (label, f) in self.point_forces {
+            to_write.push_str(&format!("point_force {} {} {}\n", label, f.0, f.1));
+        }
+
+        // body loads act on every region
+        if let Some((gx, gy)) = self.body_loads.gravity {
+            to_write.push_str(&format!("body_force gravity {} {}\n", gx, gy));
+        }
+
+        if let Some((omega, c)) = self.body_loads.rotation {
+            let c = c * self.scale;
+            to_write.push_str(&format!(
+                "body_force centrifugal {} {} {}\n",
+                omega, c.x, c.y
+            ));
+        }
+
         to_write.pop(); // pull off trailing whitespace
 
         std::fs::write(path, to_write).unwrap();